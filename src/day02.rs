@@ -1,38 +1,40 @@
+use crate::interval_set::IntervalSet;
+
 pub const INPUT: &str = include_str!("../inputs/02.txt");
 
 /// Part 1: numbers whose decimal representation is some block of digits
 /// repeated exactly twice (no leading zeroes).
 pub fn part1(input: &str) -> Result<u128, String> {
-    let ranges = merge_ranges(parse_ranges(input)?);
+    let ranges = IntervalSet::from_ranges(parse_ranges(input)?);
     Ok(if ranges.is_empty() {
         0
     } else {
-        sums_for_ranges(&ranges).0
+        sums_for_ranges(ranges.as_slice()).0
     })
 }
 
 /// Part 2: numbers whose decimal representation is a block of digits repeated
 /// at least twice (no leading zeroes).
 pub fn part2(input: &str) -> Result<u128, String> {
-    let ranges = merge_ranges(parse_ranges(input)?);
+    let ranges = IntervalSet::from_ranges(parse_ranges(input)?);
     Ok(if ranges.is_empty() {
         0
     } else {
-        sums_for_ranges(&ranges).1
+        sums_for_ranges(ranges.as_slice()).1
     })
 }
 
 /// Solve both parts with a single parse and shared candidate generation.
 pub fn both(input: &str) -> Result<(u128, u128), String> {
-    let ranges = merge_ranges(parse_ranges(input)?);
+    let ranges = IntervalSet::from_ranges(parse_ranges(input)?);
     Ok(if ranges.is_empty() {
         (0, 0)
     } else {
-        sums_for_ranges(&ranges)
+        sums_for_ranges(ranges.as_slice())
     })
 }
 
-const POW10: [u128; 20] = [
+const POW10: [u128; 21] = [
     1,
     10,
     100,
@@ -53,6 +55,7 @@ const POW10: [u128; 20] = [
     100_000_000_000_000_000,
     1_000_000_000_000_000_000,
     10_000_000_000_000_000_000,
+    100_000_000_000_000_000_000,
 ];
 
 #[inline]
@@ -147,37 +150,19 @@ fn parse_ranges(input: &str) -> Result<Vec<(u64, u64)>, String> {
     Ok(ranges)
 }
 
-fn merge_ranges(mut ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
-    if ranges.len() <= 1 {
-        return ranges;
-    }
-    ranges.sort_unstable_by_key(|&(s, _)| s);
-    let mut merged = Vec::with_capacity(ranges.len());
-    let mut current = ranges[0];
-    for &(s, e) in ranges.iter().skip(1) {
-        if s <= current.1 + 1 {
-            if e > current.1 {
-                current.1 = e;
-            }
-        } else {
-            merged.push(current);
-            current = (s, e);
-        }
-    }
-    merged.push(current);
-    merged
-}
-
 // =============================================================================
-// OPTIMIZED IMPLEMENTATION (~100x faster than original k-way merge)
+// CLOSED-FORM IMPLEMENTATION (independent of range width)
 // =============================================================================
-// Uses closed-form arithmetic summation where possible, with per-block iteration
-// only for deduplication of multi-digit blocks.
+// Every candidate is handled per total digit length `L`. Let `P(d)` be the set
+// of `L`-digit numbers made of a `d`-digit block repeated `L / d` times, i.e.
+// multiples of `(10^L - 1) / (10^d - 1)` whose block has no leading zero. Sums
+// over `P(d)` clipped to a range are a single arithmetic series.
 //
-// Key insight: Instead of generating candidates one-by-one via k-way merge,
-// we compute sums per-sequence per-range using closed-form math.
-// For block_len=1, all candidates are "fundamental" (no dedup needed).
-// For block_len>1, we check if each block is itself a repeat-block pattern.
+// Part 1 is just `P(L / 2)` for even `L`. Part 2 is the union of `P(d)` over
+// proper divisors `d` of `L`. Since `P(a) ∩ P(b) = P(gcd(a, b))`, Möbius
+// inversion over the divisor lattice of `L` gives
+//     sum(union) = -Σ_{d | L, d < L} μ(L / d) · sum(P(d))
+// so no block ever needs to be visited individually.
 
 /// Sum of integers from lo to hi inclusive: count * (lo + hi) / 2
 #[inline]
@@ -186,48 +171,38 @@ fn sum_range(lo: u64, hi: u64) -> u128 {
     count * (lo as u128 + hi as u128) / 2
 }
 
-/// Check if a block of digits is itself a repeat-block pattern.
-/// E.g., 1212 (as a 4-digit block) is 12 repeated 2 times.
-/// Returns Some((sub_block_len, sub_repeats)) if it's a repeat-block, None otherwise.
+/// Möbius function for the small arguments used here (at most 20).
 #[inline]
-fn decompose_block(block: u64, block_len: usize) -> Option<(usize, usize)> {
-    for d in 1..block_len {
-        if block_len % d != 0 {
-            continue;
-        }
-        let num_reps = block_len / d;
-        if num_reps < 2 {
-            continue;
-        }
-        let sub_rep_factor = (POW10[block_len] - 1) / (POW10[d] - 1);
-        if (block as u128) % sub_rep_factor == 0 {
-            let sub_block = (block as u128) / sub_rep_factor;
-            let sub_block_min = POW10[d - 1];
-            let sub_block_max = POW10[d] - 1;
-            if sub_block >= sub_block_min && sub_block <= sub_block_max {
-                return Some((d, num_reps));
+fn mobius(mut n: usize) -> i32 {
+    let mut result = 1;
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            n /= p;
+            if n.is_multiple_of(p) {
+                return 0;
             }
+            result = -result;
         }
+        p += 1;
+    }
+    if n > 1 {
+        result = -result;
     }
-    None
+    result
 }
 
-/// Check if a number can be represented as some block repeated exactly 2 times.
+/// Sum of the `total_len`-digit numbers in `[lo, hi]` that are a `block_len`-digit
+/// block (no leading zero) repeated `total_len / block_len` times.
 #[inline]
-fn has_double_representation(candidate: u128, total_digits: usize) -> bool {
-    if total_digits % 2 != 0 {
-        return false;
-    }
-    let half_len = total_digits / 2;
-    let half_rep_factor = (POW10[total_digits] - 1) / (POW10[half_len] - 1);
-    if candidate % half_rep_factor == 0 {
-        let half_block = candidate / half_rep_factor;
-        let half_block_min = POW10[half_len - 1];
-        let half_block_max = POW10[half_len] - 1;
-        half_block >= half_block_min && half_block <= half_block_max
-    } else {
-        false
+fn periodic_sum(lo: u64, hi: u64, block_len: usize, total_len: usize) -> u128 {
+    let rep_factor = (POW10[total_len] - 1) / (POW10[block_len] - 1);
+    let b_lo = (lo as u128).div_ceil(rep_factor).max(POW10[block_len - 1]);
+    let b_hi = (hi as u128 / rep_factor).min(POW10[block_len] - 1);
+    if b_lo > b_hi {
+        return 0;
     }
+    rep_factor * sum_range(b_lo as u64, b_hi as u64)
 }
 
 fn sums_for_ranges(ranges: &[(u64, u64)]) -> (u128, u128) {
@@ -237,69 +212,36 @@ fn sums_for_ranges(ranges: &[(u64, u64)]) -> (u128, u128) {
     let mut part1_sum = 0u128;
     let mut part2_sum = 0u128;
 
-    for block_len in 1..=max_digits {
-        let block_min = POW10[block_len - 1] as u64;
-        let block_max_possible = (POW10[block_len] - 1) as u64;
-        let max_repeats = max_digits / block_len;
-        if max_repeats < 2 {
-            continue;
-        }
+    for total_len in 2..=max_digits {
+        let len_min = POW10[total_len - 1] as u64;
+        let len_max = (POW10[total_len] - 1).min(u64::MAX as u128) as u64;
 
-        for repeats in 2..=max_repeats {
-            let total_len = block_len * repeats;
-            let rep_factor = (POW10[total_len] - 1) / (POW10[block_len] - 1);
-
-            let cap = (max_end as u128) / rep_factor;
-            if cap < block_min as u128 {
+        for &(range_start, range_end) in ranges {
+            let lo = range_start.max(len_min);
+            let hi = range_end.min(len_max);
+            if lo > hi {
                 continue;
             }
-            let block_max = cap.min(block_max_possible as u128) as u64;
-
-            for &(range_start, range_end) in ranges {
-                let b_lo = {
-                    let div = range_start as u128 / rep_factor;
-                    let rem = range_start as u128 % rep_factor;
-                    if rem == 0 {
-                        div as u64
-                    } else {
-                        (div + 1) as u64
-                    }
-                };
-                let b_hi = (range_end as u128 / rep_factor) as u64;
 
-                let b_lo = b_lo.max(block_min);
-                let b_hi = b_hi.min(block_max);
+            if total_len.is_multiple_of(2) {
+                part1_sum += periodic_sum(lo, hi, total_len / 2, total_len);
+            }
 
-                if b_lo > b_hi {
+            // Inclusion–exclusion terms can go negative individually, but the
+            // total for one (range, length) pair is always non-negative.
+            let mut added = 0u128;
+            let mut removed = 0u128;
+            for block_len in 1..total_len {
+                if !total_len.is_multiple_of(block_len) {
                     continue;
                 }
-
-                if block_len == 1 {
-                    // All 1-digit blocks are fundamental (no dedup needed)
-                    let s = rep_factor * sum_range(b_lo, b_hi);
-                    part2_sum += s;
-                    // Part 1: Check for double representation
-                    if repeats == 2 {
-                        part1_sum += s;
-                    } else if total_len % 2 == 0 {
-                        // For 1-digit block repeated even times, always has double repr
-                        part1_sum += s;
-                    }
-                } else {
-                    // Need to check each block for dedup
-                    for block in b_lo..=b_hi {
-                        let candidate = (block as u128) * rep_factor;
-                        let is_fundamental = decompose_block(block, block_len).is_none();
-
-                        if is_fundamental {
-                            part2_sum += candidate;
-                            if has_double_representation(candidate, total_len) {
-                                part1_sum += candidate;
-                            }
-                        }
-                    }
+                match mobius(total_len / block_len) {
+                    0 => {}
+                    1 => removed += periodic_sum(lo, hi, block_len, total_len),
+                    _ => added += periodic_sum(lo, hi, block_len, total_len),
                 }
             }
+            part2_sum += added - removed;
         }
     }
 
@@ -308,7 +250,7 @@ fn sums_for_ranges(ranges: &[(u64, u64)]) -> (u128, u128) {
 
 #[cfg(test)]
 mod tests {
-    use super::{part1, part2};
+    use super::{both, part1, part2};
 
     const EXAMPLE: &str = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,\n\
 1698522-1698528,446443-446449,38593856-38593862,565653-565659,\n\
@@ -325,4 +267,90 @@ mod tests {
         let input = "11-22, 99-105,\n";
         assert_eq!(part1(input).unwrap(), 132);
     }
+
+    fn brute_force(lo: u64, hi: u64) -> (u128, u128) {
+        let (mut p1, mut p2) = (0u128, 0u128);
+        for n in lo..=hi {
+            let digits = n.to_string();
+            let len = digits.len();
+            let repeats_with = |block_len: usize| {
+                digits
+                    .as_bytes()
+                    .chunks(block_len)
+                    .all(|c| c == &digits.as_bytes()[..block_len])
+            };
+            if len.is_multiple_of(2) && repeats_with(len / 2) {
+                p1 += n as u128;
+            }
+            if (1..len).any(|d| len.is_multiple_of(d) && repeats_with(d)) {
+                p2 += n as u128;
+            }
+        }
+        (p1, p2)
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        for (lo, hi) in [(1, 250_000), (999_990, 1_001_100), (12_344_000, 12_346_000)] {
+            let input = format!("{}-{}", lo, hi);
+            assert_eq!(
+                both(&input).unwrap(),
+                brute_force(lo, hi),
+                "range {}",
+                input
+            );
+        }
+    }
+
+    /// Totals over `1..=max` by a different route than `sums_for_ranges`:
+    /// part 2 sums numbers by their smallest repeating block, each length's
+    /// share found by subtracting the shares of its divisors.
+    fn by_smallest_block(max: u64) -> (u128, u128) {
+        let repeated = |total_len: u32, block_len: u32| {
+            let rep = (10u128.pow(total_len) - 1) / (10u128.pow(block_len) - 1);
+            let lo = 10u128.pow(block_len - 1);
+            let hi = (10u128.pow(block_len) - 1).min(max as u128 / rep);
+            if lo > hi {
+                0
+            } else {
+                rep * (lo + hi) * (hi - lo + 1) / 2
+            }
+        };
+        let (mut p1, mut p2) = (0u128, 0u128);
+        for total_len in 2..=max.to_string().len() as u32 {
+            if total_len.is_multiple_of(2) {
+                p1 += repeated(total_len, total_len / 2);
+            }
+            let mut smallest = vec![0u128; total_len as usize];
+            for block_len in 1..total_len {
+                if !total_len.is_multiple_of(block_len) {
+                    continue;
+                }
+                let shorter: u128 = (1..block_len)
+                    .filter(|d| block_len.is_multiple_of(*d))
+                    .map(|d| smallest[d as usize])
+                    .sum();
+                smallest[block_len as usize] = repeated(total_len, block_len) - shorter;
+                p2 += smallest[block_len as usize];
+            }
+        }
+        (p1, p2)
+    }
+
+    #[test]
+    fn handles_full_u64_range() {
+        assert_eq!(by_smallest_block(999_999), brute_force(1, 999_999));
+        assert_eq!(by_smallest_block(999_999), (495_540_450, 540_590_850));
+
+        // Would take forever if any per-block work remained.
+        let full = both("1-18446744073709551615").unwrap();
+        assert_eq!(full, by_smallest_block(u64::MAX));
+        assert_eq!(
+            full,
+            (
+                12_509_613_850_169_742_155_792_778_978,
+                12_510_120_345_498_176_585_131_248_687
+            )
+        );
+    }
 }
@@ -1,7 +1,13 @@
+use std::fmt;
+use std::io::BufRead;
+
 pub static INPUT: &str = include_str!("../inputs/03.txt");
 const PART2_DIGITS: usize = 12;
 const STACK_CAP: usize = 128;
 
+/// Fixed-capacity monotonic stack for the hot paths. The stack never holds more
+/// than the pick count: once full, a digit that cannot displace anything is
+/// dropped and charged to the removal budget, so line length is unbounded.
 #[derive(Clone)]
 struct DigitStack {
     buf: [u8; STACK_CAP],
@@ -82,6 +88,57 @@ pub fn both(input: &str) -> Result<(u64, u64), String> {
     Ok((total_2, total_12))
 }
 
+/// Running totals reported by [`both_from_reader`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StreamTotals {
+    /// Non-empty lines processed so far.
+    pub lines: u64,
+    pub part1: u128,
+    pub part2: u128,
+}
+
+/// Streaming variant of [`both`] for inputs too large to hold in memory. Lines
+/// are read one at a time into a reused buffer, so memory is bounded by the
+/// longest line. `on_line` sees the running totals after every non-empty line.
+pub fn both_from_reader<R: BufRead>(
+    mut reader: R,
+    mut on_line: impl FnMut(&StreamTotals),
+) -> Result<StreamTotals, String> {
+    let mut totals = StreamTotals::default();
+    let mut stack_2 = DigitStack::new();
+    let mut stack_12 = DigitStack::new();
+    let mut line = Vec::new();
+    let mut line_idx = 0;
+
+    loop {
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .map_err(|err| format!("failed to read line {}: {}", line_idx + 1, err))?;
+        if read == 0 {
+            break;
+        }
+        let mut end = line.len();
+        if end > 0 && line[end - 1] == b'\n' {
+            end -= 1;
+        }
+        if end > 0 && line[end - 1] == b'\r' {
+            end -= 1;
+        }
+        if end > 0 {
+            let (v2, v12) =
+                max_numbers_from_line(&line[..end], line_idx, &mut stack_2, &mut stack_12)?;
+            totals.lines += 1;
+            totals.part1 += v2 as u128;
+            totals.part2 += v12 as u128;
+            on_line(&totals);
+        }
+        line_idx += 1;
+    }
+
+    Ok(totals)
+}
+
 #[inline(always)]
 fn max_numbers_from_line(
     line: &[u8],
@@ -128,8 +185,12 @@ fn max_numbers_from_line(
             stack_2.len -= 1;
             remove_2 -= 1;
         }
-        unsafe { *stack_2.buf.get_unchecked_mut(stack_2.len) = digit };
-        stack_2.len += 1;
+        if stack_2.len < 2 {
+            unsafe { *stack_2.buf.get_unchecked_mut(stack_2.len) = digit };
+            stack_2.len += 1;
+        } else {
+            remove_2 -= 1;
+        }
 
         while remove_12 > 0
             && stack_12.len > 0
@@ -138,15 +199,12 @@ fn max_numbers_from_line(
             stack_12.len -= 1;
             remove_12 -= 1;
         }
-        unsafe { *stack_12.buf.get_unchecked_mut(stack_12.len) = digit };
-        stack_12.len += 1;
-    }
-
-    if remove_2 > 0 {
-        stack_2.len -= remove_2;
-    }
-    if remove_12 > 0 {
-        stack_12.len -= remove_12;
+        if stack_12.len < PART2_DIGITS {
+            unsafe { *stack_12.buf.get_unchecked_mut(stack_12.len) = digit };
+            stack_12.len += 1;
+        } else {
+            remove_12 -= 1;
+        }
     }
 
     let value_2 = (unsafe { *stack_2.buf.get_unchecked(0) } as u64) * 10
@@ -229,12 +287,12 @@ fn max_number_from_line_pick<const PICK: usize>(
             stack.len -= 1;
             remove -= 1;
         }
-        unsafe { *stack.buf.get_unchecked_mut(stack.len) = digit };
-        stack.len += 1;
-    }
-
-    if remove > 0 {
-        stack.len -= remove;
+        if stack.len < PICK {
+            unsafe { *stack.buf.get_unchecked_mut(stack.len) = digit };
+            stack.len += 1;
+        } else {
+            remove -= 1;
+        }
     }
 
     let mut value: u64 = 0;
@@ -244,9 +302,186 @@ fn max_number_from_line_pick<const PICK: usize>(
     Ok(value)
 }
 
+// =============================================================================
+// GENERAL PICK COUNT
+// =============================================================================
+// Same greedy monotonic stack as above, but heap-backed so any pick count and
+// line length work. Used for custom `k` and for recovering the chosen digits.
+
+/// Lexicographically largest `k`-digit subsequence of one line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Subsequence {
+    /// Chosen digits (0-9), most significant first.
+    pub digits: Vec<u8>,
+    /// Position of each chosen digit within the line, if requested.
+    pub indices: Option<Vec<usize>>,
+}
+
+impl Subsequence {
+    /// Numeric value, or `None` if it does not fit in a `u128`.
+    pub fn to_u128(&self) -> Option<u128> {
+        self.digits
+            .iter()
+            .try_fold(0u128, |acc, &d| acc.checked_mul(10)?.checked_add(d as u128))
+    }
+
+    /// Decimal representation without leading zeroes.
+    pub fn to_decimal_string(&self) -> String {
+        digits_to_string(self.digits.iter().copied())
+    }
+}
+
+/// Sum of per-line maxima: an integer while it fits, decimal text beyond that.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PickTotal {
+    Int(u128),
+    Decimal(String),
+}
+
+impl fmt::Display for PickTotal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PickTotal::Int(n) => write!(f, "{}", n),
+            PickTotal::Decimal(s) => f.write_str(s),
+        }
+    }
+}
+
+/// Largest number formed by picking `k` digits of `line` in order.
+pub fn max_subsequence(line: &str, k: usize) -> Result<Subsequence, String> {
+    pick_from_line(line.trim_end().as_bytes(), 0, k, false)
+}
+
+/// Like [`max_subsequence`], also reporting where each chosen digit came from.
+pub fn max_subsequence_with_indices(line: &str, k: usize) -> Result<Subsequence, String> {
+    pick_from_line(line.trim_end().as_bytes(), 0, k, true)
+}
+
+/// Sum the best `k`-digit pick of every line. Totals are kept in a `u128` for
+/// `k <= 19` and as an exact decimal string for longer picks.
+pub fn solve_pick(input: &str, k: usize) -> Result<PickTotal, String> {
+    let mut small: u128 = 0;
+    // Little-endian decimal digits, only used when values can exceed a u64.
+    let mut big: Vec<u8> = Vec::new();
+
+    for (line_idx, line) in input.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let picked = pick_from_line(line.as_bytes(), line_idx, k, false)?;
+        if k <= 19 {
+            let value = picked.to_u128().unwrap_or_else(|| unreachable!());
+            small = small
+                .checked_add(value)
+                .ok_or_else(|| format!("total overflowed u128 at line {}", line_idx + 1))?;
+        } else {
+            add_decimal(&mut big, &picked.digits);
+        }
+    }
+
+    Ok(if k <= 19 {
+        PickTotal::Int(small)
+    } else {
+        PickTotal::Decimal(digits_to_string(big.iter().rev().copied()))
+    })
+}
+
+fn pick_from_line(
+    line: &[u8],
+    line_idx: usize,
+    k: usize,
+    track_indices: bool,
+) -> Result<Subsequence, String> {
+    if k == 0 {
+        return Err("pick count must be at least 1".into());
+    }
+    if line.len() < k {
+        return Err(format!(
+            "line {} must contain at least {} digits, found {}",
+            line_idx + 1,
+            k,
+            line.len()
+        ));
+    }
+
+    let mut remove = line.len() - k;
+    let mut digits: Vec<u8> = Vec::with_capacity(k);
+    let mut indices: Vec<usize> = Vec::with_capacity(if track_indices { k } else { 0 });
+
+    for (pos, &b) in line.iter().enumerate() {
+        if !b.is_ascii_digit() {
+            return Err(format!(
+                "line {} contains non-digit character '{}'",
+                line_idx + 1,
+                b as char
+            ));
+        }
+        let digit = b - b'0';
+        while remove > 0 && digits.last().is_some_and(|&top| top < digit) {
+            digits.pop();
+            if track_indices {
+                indices.pop();
+            }
+            remove -= 1;
+        }
+        if digits.len() < k {
+            digits.push(digit);
+            if track_indices {
+                indices.push(pos);
+            }
+        } else {
+            remove -= 1;
+        }
+    }
+
+    Ok(Subsequence {
+        digits,
+        indices: track_indices.then_some(indices),
+    })
+}
+
+/// Add big-endian `digits` into the little-endian accumulator `acc`.
+fn add_decimal(acc: &mut Vec<u8>, digits: &[u8]) {
+    if acc.len() < digits.len() {
+        acc.resize(digits.len(), 0);
+    }
+    let mut carry = 0u8;
+    let mut i = 0;
+    for &d in digits.iter().rev() {
+        let sum = acc[i] + d + carry;
+        acc[i] = sum % 10;
+        carry = sum / 10;
+        i += 1;
+    }
+    while carry > 0 {
+        if i == acc.len() {
+            acc.push(0);
+        }
+        let sum = acc[i] + carry;
+        acc[i] = sum % 10;
+        carry = sum / 10;
+        i += 1;
+    }
+}
+
+fn digits_to_string(digits: impl Iterator<Item = u8>) -> String {
+    let s: String = digits
+        .skip_while(|&d| d == 0)
+        .map(|d| (b'0' + d) as char)
+        .collect();
+    if s.is_empty() {
+        "0".to_string()
+    } else {
+        s
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{part1, part2};
+    use super::{
+        both, both_from_reader, max_subsequence_with_indices, part1, part2, solve_pick, PickTotal,
+        INPUT,
+    };
 
     #[test]
     fn example_input_part1() {
@@ -271,4 +506,48 @@ mod tests {
 
         assert_eq!(part2(input).unwrap(), 3_121_910_778_619);
     }
+
+    #[test]
+    fn arbitrary_pick_counts() {
+        let input = "\
+987654321111111
+811111111111119
+234234234234278
+818181911112111
+";
+
+        assert_eq!(solve_pick(input, 2).unwrap(), PickTotal::Int(357));
+        assert_eq!(
+            solve_pick(input, 12).unwrap(),
+            PickTotal::Int(3_121_910_778_619)
+        );
+        assert_eq!(
+            solve_pick(input, 15).unwrap().to_string(),
+            "2851181577568619"
+        );
+
+        let long = "9".repeat(300) + "\n" + &"1".repeat(300) + "\n";
+        let expected = format!("1{}0", "1".repeat(199));
+        assert_eq!(
+            solve_pick(&long, 200).unwrap(),
+            PickTotal::Decimal(expected)
+        );
+
+        let picked = max_subsequence_with_indices("818181911112111", 5).unwrap();
+        assert_eq!(picked.digits, vec![9, 2, 1, 1, 1]);
+        assert_eq!(picked.indices, Some(vec![6, 11, 12, 13, 14]));
+    }
+
+    #[test]
+    fn streaming_matches_in_memory() {
+        let input = "987654321111111\r\n811111111111119\n\n234234234234278\n818181911112111";
+        let mut seen = Vec::new();
+        let totals = both_from_reader(input.as_bytes(), |t| seen.push(t.part1)).unwrap();
+        assert_eq!((totals.part1, totals.part2), (357, 3_121_910_778_619));
+        assert_eq!(seen, vec![98, 187, 265, 357]);
+
+        let (p1, p2) = both(INPUT).unwrap();
+        let totals = both_from_reader(std::io::BufReader::new(INPUT.as_bytes()), |_| {}).unwrap();
+        assert_eq!((totals.part1, totals.part2), (p1 as u128, p2 as u128));
+    }
 }
@@ -16,12 +16,20 @@ pub fn part2(input: &str) -> Result<usize, String> {
 /// Solve both parts with a single parse and neighbor pass.
 pub fn both(input: &str) -> Result<(usize, usize), String> {
     let mut grid = parse_grid(input)?;
-    let mut counts = neighbor_counts(&grid);
+    Ok(remove_all(&mut grid, &Moore, 4))
+}
+
+/// Queue-based removal shared by [`both`] and [`both_with_rules`]: a roll is
+/// accessible while fewer than `threshold` of its neighbors hold rolls.
+/// Returns the initially accessible and the total removed rolls.
+#[inline(always)]
+fn remove_all<N: Neighbors>(grid: &mut Grid, neighbors: &N, threshold: u8) -> (usize, usize) {
+    let mut counts = neighbors.counts(grid);
     let mut queue = Vec::with_capacity(grid.data.len());
     let mut accessible = 0usize;
 
     for (idx, (&cell, &count)) in grid.data.iter().zip(&counts).enumerate() {
-        if cell == 1 && count < 4 {
+        if cell == 1 && count < threshold {
             accessible += 1;
             queue.push(idx);
         }
@@ -32,19 +40,536 @@ pub fn both(input: &str) -> Result<(usize, usize), String> {
     while head < queue.len() {
         let idx = queue[head];
         head += 1;
-        if grid.data[idx] == 0 || counts[idx] >= 4 {
+        if grid.data[idx] == 0 || counts[idx] >= threshold {
             continue;
         }
 
         grid.data[idx] = 0;
         removed += 1;
 
-        update_neighbors(idx, &mut grid, &mut counts, &mut queue);
+        release(idx, grid, neighbors, threshold, &mut counts, &mut queue);
+    }
+
+    (accessible, removed)
+}
+
+/// Which cells count as neighbors of a roll.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// The four orthogonally adjacent cells.
+    VonNeumann,
+    /// The eight surrounding cells (the puzzle's rule).
+    Moore,
+    /// Arbitrary `(row, col)` offsets; `(0, 0)` is rejected.
+    Custom(Vec<(isize, isize)>),
+}
+
+/// How neighbor offsets behave at the grid border.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edges {
+    /// Offsets that leave the grid are ignored.
+    Bounded,
+    /// Offsets wrap around to the opposite side.
+    Toroidal,
+}
+
+/// Accessibility rule: a roll is accessible when fewer than `threshold` of its
+/// neighbors hold rolls. The default is the puzzle's (Moore, 4, bounded).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    pub neighborhood: Neighborhood,
+    pub threshold: u32,
+    pub edges: Edges,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            neighborhood: Neighborhood::Moore,
+            threshold: 4,
+            edges: Edges::Bounded,
+        }
+    }
+}
+
+impl Rules {
+    fn offsets(&self) -> Result<Vec<(isize, isize)>, String> {
+        Ok(match &self.neighborhood {
+            Neighborhood::VonNeumann => vec![(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighborhood::Moore => MOORE.to_vec(),
+            Neighborhood::Custom(offsets) => {
+                if offsets.contains(&(0, 0)) {
+                    return Err("custom neighborhood must not contain offset (0, 0)".into());
+                }
+                for (i, offset) in offsets.iter().enumerate() {
+                    if offsets[..i].contains(offset) {
+                        return Err(format!(
+                            "custom neighborhood lists offset {:?} twice",
+                            offset
+                        ));
+                    }
+                }
+                if offsets.len() >= u8::MAX as usize {
+                    return Err(format!(
+                        "custom neighborhood has {} offsets, at most {} are supported",
+                        offsets.len(),
+                        u8::MAX - 1
+                    ));
+                }
+                offsets.clone()
+            }
+        })
+    }
+}
+
+/// Solve both parts under custom [`Rules`] with the same queue engine as
+/// [`both`]; bounded Moore neighborhoods keep its unrolled neighbor walk.
+///
+/// On a torus every offset must reach a distinct cell other than the roll
+/// itself, so grids narrower than the neighborhood (e.g. a Moore torus of
+/// width or height 2 or less) are rejected.
+pub fn both_with_rules(input: &str, rules: &Rules) -> Result<(usize, usize), String> {
+    let offsets = rules.offsets()?;
+    let mut grid = parse_grid(input)?;
+    // No roll can have more than `offsets.len()` neighbors.
+    let threshold = rules.threshold.min(offsets.len() as u32 + 1) as u8;
+    if rules.neighborhood == Neighborhood::Moore && rules.edges == Edges::Bounded {
+        return Ok(remove_all(&mut grid, &Moore, threshold));
+    }
+    let neighbors = Offsets::new(offsets, rules.edges, grid.width, grid.height)?;
+    Ok(remove_all(&mut grid, &neighbors, threshold))
+}
+
+const MOORE: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Neighbor geometry for [`remove_all`].
+trait Neighbors {
+    /// Number of neighboring rolls of every roll (0 for empty cells).
+    fn counts(&self, grid: &Grid) -> Vec<u8>;
+    /// Call `f` with every cell that has `idx` among its neighbors.
+    fn for_each_watcher(&self, idx: usize, width: usize, height: usize, f: impl FnMut(usize));
+}
+
+/// The puzzle's bounded eight-cell neighborhood, walked without offsets.
+struct Moore;
+
+impl Neighbors for Moore {
+    fn counts(&self, grid: &Grid) -> Vec<u8> {
+        neighbor_counts(grid)
+    }
+
+    #[inline(always)]
+    fn for_each_watcher(&self, idx: usize, w: usize, h: usize, mut f: impl FnMut(usize)) {
+        let r = idx / w;
+        let c = idx - r * w;
+        if c > 0 {
+            f(idx - 1);
+            if r > 0 {
+                f(idx - w - 1);
+            }
+            if r + 1 < h {
+                f(idx + w - 1);
+            }
+        }
+        if c + 1 < w {
+            f(idx + 1);
+            if r > 0 {
+                f(idx - w + 1);
+            }
+            if r + 1 < h {
+                f(idx + w + 1);
+            }
+        }
+        if r > 0 {
+            f(idx - w);
+        }
+        if r + 1 < h {
+            f(idx + w);
+        }
+    }
+}
+
+/// An explicit offset list, bounded or toroidal.
+struct Offsets {
+    offsets: Vec<(isize, isize)>,
+    edges: Edges,
+    width: isize,
+    height: isize,
+}
+
+impl Offsets {
+    fn new(
+        offsets: Vec<(isize, isize)>,
+        edges: Edges,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, String> {
+        let (w, h) = (width as isize, height as isize);
+        if edges == Edges::Toroidal {
+            let wrapped: Vec<(isize, isize)> = offsets
+                .iter()
+                .map(|&(dr, dc)| (dr.rem_euclid(h), dc.rem_euclid(w)))
+                .collect();
+            for (i, cell) in wrapped.iter().enumerate() {
+                if *cell == (0, 0) {
+                    return Err(format!(
+                        "toroidal offset {:?} wraps onto the roll itself on a {}x{} grid",
+                        offsets[i], width, height
+                    ));
+                }
+                if let Some(j) = wrapped[..i].iter().position(|other| other == cell) {
+                    return Err(format!(
+                        "toroidal offsets {:?} and {:?} reach the same cell on a {}x{} grid",
+                        offsets[j], offsets[i], width, height
+                    ));
+                }
+            }
+        }
+        Ok(Self {
+            offsets,
+            edges,
+            width: w,
+            height: h,
+        })
+    }
+
+    #[inline(always)]
+    fn shift(&self, idx: usize, (dr, dc): (isize, isize)) -> Option<usize> {
+        let (w, h) = (self.width, self.height);
+        let r = (idx as isize / w) + dr;
+        let c = (idx as isize % w) + dc;
+        match self.edges {
+            Edges::Bounded => {
+                ((0..h).contains(&r) && (0..w).contains(&c)).then(|| (r * w + c) as usize)
+            }
+            Edges::Toroidal => Some((r.rem_euclid(h) * w + c.rem_euclid(w)) as usize),
+        }
+    }
+}
+
+impl Neighbors for Offsets {
+    fn counts(&self, grid: &Grid) -> Vec<u8> {
+        let mut counts = vec![0u8; grid.data.len()];
+        for (idx, count) in counts.iter_mut().enumerate() {
+            if grid.data[idx] == 1 {
+                *count = self
+                    .offsets
+                    .iter()
+                    .filter_map(|&o| self.shift(idx, o))
+                    .filter(|&n| grid.data[n] == 1)
+                    .count() as u8;
+            }
+        }
+        counts
+    }
+
+    fn for_each_watcher(&self, idx: usize, _: usize, _: usize, mut f: impl FnMut(usize)) {
+        // Offsets need not be symmetric: the cells that count `idx` are the
+        // ones at `idx - offset`.
+        for &(dr, dc) in &self.offsets {
+            if let Some(n) = self.shift(idx, (-dr, -dc)) {
+                f(n);
+            }
+        }
+    }
+}
+
+/// Engine used by [`both_with_engine`]. Both give identical answers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// Byte-per-cell neighbor counts and a removal queue; fastest on
+    /// puzzle-sized grids.
+    #[default]
+    Queue,
+    /// Bit-packed rows with word-parallel neighbor counting and a word-level
+    /// frontier; meant for grids with tens of millions of cells.
+    BitParallel,
+}
+
+/// Solve both parts with the chosen engine.
+pub fn both_with_engine(input: &str, engine: Engine) -> Result<(usize, usize), String> {
+    match engine {
+        Engine::Queue => both(input),
+        Engine::BitParallel => both_bits(input),
+    }
+}
+
+// =============================================================================
+// BIT-PARALLEL ENGINE
+// =============================================================================
+// Each row is packed into u64 words (column `c` is bit `c % 64` of word
+// `c / 64`). The eight neighbor bitboards of a word are summed with a
+// carry-save adder tree, so 64 cells are classified per handful of ops.
+//
+// Part 2 clears a word's accessible rolls in place (the stable grid does not
+// depend on removal order) and only re-examines words next to a change.
+
+struct BitGrid {
+    words: Vec<u64>,
+    words_per_row: usize,
+    height: usize,
+}
+
+impl BitGrid {
+    /// Rolls in word `idx` with fewer than four rolls among their neighbors.
+    #[inline(always)]
+    fn accessible(&self, idx: usize) -> u64 {
+        let center = self.words[idx];
+        if center == 0 {
+            return 0;
+        }
+        let wpr = self.words_per_row;
+        let row = idx / wpr;
+        let k = idx % wpr;
+        let (has_west, has_east) = (k > 0, k + 1 < wpr);
+        // (west-shifted, same, east-shifted) bitboards for the row containing `at`.
+        let shifted = |at: usize| {
+            let mid = self.words[at];
+            let prev = if has_west { self.words[at - 1] } else { 0 };
+            let next = if has_east { self.words[at + 1] } else { 0 };
+            ((mid << 1) | (prev >> 63), mid, (mid >> 1) | (next << 63))
+        };
+
+        let (n0, n1, n2) = if row > 0 {
+            shifted(idx - wpr)
+        } else {
+            (0, 0, 0)
+        };
+        let (n3, _, n4) = shifted(idx);
+        let (n5, n6, n7) = if row + 1 < self.height {
+            shifted(idx + wpr)
+        } else {
+            (0, 0, 0)
+        };
+
+        // Carry-save tree: only "count >= 4" is needed, which is exactly
+        // the OR of the two weight-4 carries.
+        let (s1, c1) = full_add(n0, n1, n2);
+        let (s2, c2) = full_add(n3, n4, n5);
+        let (s3, c3) = (n6 ^ n7, n6 & n7);
+        let (_, c4) = full_add(s1, s2, s3);
+        let (t, c5) = full_add(c1, c2, c3);
+        let c6 = t & c4;
+        center & !(c5 | c6)
+    }
+}
+
+#[inline(always)]
+fn full_add(a: u64, b: u64, c: u64) -> (u64, u64) {
+    let ab = a ^ b;
+    (ab ^ c, (a & b) | (c & ab))
+}
+
+fn parse_bit_grid(input: &str) -> Result<BitGrid, String> {
+    let mut words = Vec::new();
+    let mut width = 0usize;
+    let mut words_per_row = 0usize;
+    let mut height = 0usize;
+
+    for line in input.lines() {
+        let line = line.as_bytes();
+        if line.is_empty() {
+            continue;
+        }
+        if width == 0 {
+            width = line.len();
+            words_per_row = width.div_ceil(64);
+        } else if line.len() != width {
+            return Err(format!(
+                "inconsistent row width: expected {}, found {} on row {}",
+                width,
+                line.len(),
+                height + 1
+            ));
+        }
+        for chunk in line.chunks(64) {
+            let mut word = 0u64;
+            let mut invalid = false;
+            for (bit, &b) in chunk.iter().enumerate() {
+                word |= ((b == b'@') as u64) << bit;
+                invalid |= b != b'@' && b != b'.';
+            }
+            if invalid {
+                let col = line
+                    .iter()
+                    .position(|&b| b != b'@' && b != b'.')
+                    .unwrap_or(0);
+                return Err(format!(
+                    "invalid character '{}' at line {} column {}",
+                    line[col] as char,
+                    height + 1,
+                    col + 1
+                ));
+            }
+            words.push(word);
+        }
+        height += 1;
+    }
+
+    if width == 0 || height == 0 {
+        return Err("input is empty".into());
+    }
+
+    Ok(BitGrid {
+        words,
+        words_per_row,
+        height,
+    })
+}
+
+fn both_bits(input: &str) -> Result<(usize, usize), String> {
+    let mut grid = parse_bit_grid(input)?;
+    let wpr = grid.words_per_row;
+    let len = grid.words.len();
+
+    let accessible = (0..len)
+        .map(|idx| grid.accessible(idx).count_ones() as usize)
+        .sum();
+
+    // Worklist of words that may hold accessible rolls. Removals are applied
+    // immediately; any word whose neighborhood changed is re-examined.
+    let mut frontier: Vec<usize> = (0..len).rev().collect();
+    let mut queued = vec![true; len];
+    let mut removed = 0usize;
+
+    while let Some(idx) = frontier.pop() {
+        queued[idx] = false;
+        // Settle this word first: clearing rolls can free others in it.
+        let mut cleared = 0u64;
+        loop {
+            let mask = grid.accessible(idx);
+            if mask == 0 {
+                break;
+            }
+            grid.words[idx] &= !mask;
+            cleared |= mask;
+        }
+        if cleared == 0 {
+            continue;
+        }
+        removed += cleared.count_ones() as usize;
+
+        // Only words whose cells border a cleared bit need another look.
+        let row = idx / wpr;
+        let k = idx % wpr;
+        let first = if cleared & 1 != 0 {
+            k.saturating_sub(1)
+        } else {
+            k
+        };
+        let last = if cleared >> 63 != 0 {
+            (k + 1).min(wpr - 1)
+        } else {
+            k
+        };
+        for r in row.saturating_sub(1)..=(row + 1).min(grid.height - 1) {
+            for kk in first..=last {
+                let n = r * wpr + kk;
+                if n != idx && !queued[n] {
+                    queued[n] = true;
+                    frontier.push(n);
+                }
+            }
+        }
     }
 
     Ok((accessible, removed))
 }
 
+/// Part 2 replayed in synchronous rounds: every roll that is accessible at the
+/// start of a round is removed together, forming one wave. Iterating yields the
+/// `(row, col)` cells of each wave; once exhausted the grid is stable and the
+/// wave sizes sum to the part 2 answer.
+pub struct Waves {
+    grid: Grid,
+    counts: Vec<u8>,
+    frontier: Vec<usize>,
+}
+
+impl Waves {
+    pub fn new(input: &str) -> Result<Self, String> {
+        let grid = parse_grid(input)?;
+        let counts = neighbor_counts(&grid);
+        let frontier = grid
+            .data
+            .iter()
+            .zip(&counts)
+            .enumerate()
+            .filter(|(_, (&cell, &count))| cell == 1 && count < 4)
+            .map(|(idx, _)| idx)
+            .collect();
+        Ok(Self {
+            grid,
+            counts,
+            frontier,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.grid.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.grid.height
+    }
+
+    /// Whether `(row, col)` still holds a roll.
+    pub fn is_roll(&self, row: usize, col: usize) -> bool {
+        self.grid.data[row * self.grid.width + col] == 1
+    }
+
+    /// Current grid as `@`/`.` rows, with the given cells drawn as `x`.
+    pub fn frame(&self, marked: &[(usize, usize)]) -> String {
+        let w = self.grid.width;
+        let mut out = Vec::with_capacity((w + 1) * self.grid.height);
+        for row in self.grid.data.chunks(w) {
+            out.extend(row.iter().map(|&c| if c == 1 { b'@' } else { b'.' }));
+            out.push(b'\n');
+        }
+        for &(r, c) in marked {
+            out[r * (w + 1) + c] = b'x';
+        }
+        String::from_utf8(out).unwrap_or_default()
+    }
+}
+
+impl Iterator for Waves {
+    type Item = Vec<(usize, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.frontier.is_empty() {
+            return None;
+        }
+        let wave = std::mem::take(&mut self.frontier);
+        // Clear the whole wave first so rolls removed together do not count
+        // each other as newly accessible.
+        for &idx in &wave {
+            self.grid.data[idx] = 0;
+        }
+        for &idx in &wave {
+            release(
+                idx,
+                &self.grid,
+                &Moore,
+                4,
+                &mut self.counts,
+                &mut self.frontier,
+            );
+        }
+        let w = self.grid.width;
+        Some(wave.into_iter().map(|idx| (idx / w, idx % w)).collect())
+    }
+}
+
 #[derive(Clone)]
 struct Grid {
     data: Vec<u8>,
@@ -182,54 +707,37 @@ fn neighbor_counts(grid: &Grid) -> Vec<u8> {
     counts
 }
 
+/// Drop the neighbor count of every roll that counted the removed `idx`,
+/// queueing those that just became accessible.
 #[inline(always)]
-fn update_neighbors(idx: usize, grid: &mut Grid, counts: &mut [u8], queue: &mut Vec<usize>) {
-    let w = grid.width;
-    let h = grid.height;
-    let r = idx / w;
-    let c = idx - r * w;
-
-    let mut push_neighbor = |n_idx: usize| {
+fn release<N: Neighbors>(
+    idx: usize,
+    grid: &Grid,
+    neighbors: &N,
+    threshold: u8,
+    counts: &mut [u8],
+    queue: &mut Vec<usize>,
+) {
+    neighbors.for_each_watcher(idx, grid.width, grid.height, |n_idx| {
         if grid.data[n_idx] == 1 {
             let val = &mut counts[n_idx];
             if *val > 0 {
                 *val -= 1;
             }
-            if *val == 3 {
+            if *val + 1 == threshold {
                 queue.push(n_idx);
             }
         }
-    };
-
-    if c > 0 {
-        push_neighbor(idx - 1);
-        if r > 0 {
-            push_neighbor(idx - w - 1);
-        }
-        if r + 1 < h {
-            push_neighbor(idx + w - 1);
-        }
-    }
-    if c + 1 < w {
-        push_neighbor(idx + 1);
-        if r > 0 {
-            push_neighbor(idx - w + 1);
-        }
-        if r + 1 < h {
-            push_neighbor(idx + w + 1);
-        }
-    }
-    if r > 0 {
-        push_neighbor(idx - w);
-    }
-    if r + 1 < h {
-        push_neighbor(idx + w);
-    }
+    });
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{part1, part2};
+    use super::{
+        both, both_with_engine, both_with_rules, part1, part2, Edges, Engine, Neighborhood, Rules,
+        Waves, INPUT,
+    };
+    use crate::test_rng::XorShift;
 
     const EXAMPLE: &str = "\
 ..@@.@@@@.
@@ -253,4 +761,98 @@ mod tests {
     fn example_total_removed() {
         assert_eq!(part2(EXAMPLE).unwrap(), 43);
     }
+
+    #[test]
+    fn waves_remove_same_rolls_as_part2() {
+        let mut waves = Waves::new(EXAMPLE).unwrap();
+        let sizes: Vec<usize> = waves.by_ref().map(|wave| wave.len()).collect();
+        assert_eq!(sizes[0], 13);
+        assert_eq!(sizes.iter().sum::<usize>(), 43);
+        let remaining = waves.frame(&[]).bytes().filter(|&b| b == b'@').count();
+        assert_eq!(
+            remaining,
+            EXAMPLE.bytes().filter(|&b| b == b'@').count() - 43
+        );
+    }
+
+    #[test]
+    fn custom_rules() {
+        let moore = vec![
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, -1),
+            (0, 1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+        ];
+        let explicit = Rules {
+            neighborhood: Neighborhood::Custom(moore),
+            ..Rules::default()
+        };
+        assert_eq!(both_with_rules(EXAMPLE, &explicit).unwrap(), (13, 43));
+
+        let full = "@@@\n@@@\n@@@\n";
+        let torus = Rules {
+            edges: Edges::Toroidal,
+            ..Rules::default()
+        };
+        assert_eq!(both_with_rules(full, &Rules::default()).unwrap(), (4, 9));
+        assert_eq!(both_with_rules(full, &torus).unwrap(), (0, 0));
+        // Every roll is accessible once the threshold exceeds the neighborhood.
+        let lenient = Rules {
+            threshold: 20,
+            ..Rules::default()
+        };
+        assert_eq!(both_with_rules(full, &lenient).unwrap(), (9, 9));
+        // A 2-wide torus would count the same neighbor from both sides.
+        assert_eq!(
+            both_with_rules("@@\n@@\n@@\n", &torus).err().unwrap(),
+            "toroidal offsets (-1, -1) and (-1, 1) reach the same cell on a 2x3 grid"
+        );
+
+        // Rolls only "see" the cell to their right: the rightmost column goes
+        // first, then removal sweeps leftwards across the whole row.
+        let right_only = Rules {
+            neighborhood: Neighborhood::Custom(vec![(0, 1)]),
+            threshold: 1,
+            edges: Edges::Bounded,
+        };
+        assert_eq!(both_with_rules("@@@@\n", &right_only).unwrap(), (1, 4));
+        assert!(both_with_rules(
+            full,
+            &Rules {
+                neighborhood: Neighborhood::Custom(vec![(0, 0)]),
+                ..Rules::default()
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn bit_parallel_engine_matches_queue() {
+        assert_eq!(
+            both_with_engine(EXAMPLE, Engine::BitParallel).unwrap(),
+            (13, 43)
+        );
+        assert_eq!(
+            both_with_engine(INPUT, Engine::BitParallel).unwrap(),
+            both(INPUT).unwrap()
+        );
+
+        // Pseudo-random grid whose width straddles several word boundaries.
+        let mut rng = XorShift::new(0x2545_f491_4f6c_dd1d);
+        let mut grid = String::new();
+        for _ in 0..150 {
+            for _ in 0..197 {
+                grid.push(if rng.below(10) < 7 { '@' } else { '.' });
+            }
+            grid.push('\n');
+        }
+        assert_eq!(
+            both_with_engine(&grid, Engine::BitParallel).unwrap(),
+            both(&grid).unwrap()
+        );
+    }
 }
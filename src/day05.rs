@@ -1,13 +1,17 @@
+use crate::interval_set::IntervalSet;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+
 pub static INPUT: &str = include_str!("../inputs/05.txt");
 
 /// Part 1: count available ingredient IDs that fall within any fresh range.
 pub fn part1(input: &str) -> Result<usize, String> {
     let (ranges, ids) = parse_input(input)?;
-    let merged = merge_ranges(ranges);
+    let fresh_ranges = IntervalSet::from_ranges(ranges);
 
     let mut fresh = 0usize;
     for id in ids {
-        fresh += is_fresh(id, &merged) as usize;
+        fresh += fresh_ranges.contains(id) as usize;
     }
 
     Ok(fresh)
@@ -16,37 +20,264 @@ pub fn part1(input: &str) -> Result<usize, String> {
 /// Part 2: count how many IDs are fresh across all ranges (size of the union).
 pub fn part2(input: &str) -> Result<u128, String> {
     let ranges = parse_ranges(input)?;
-    let merged = merge_ranges(ranges);
-
-    let mut total: u128 = 0;
-    for (start, end) in merged {
-        total += (end - start + 1) as u128;
-    }
-
-    Ok(total)
+    Ok(IntervalSet::from_ranges(ranges).count_covered())
 }
 
 /// Solve both parts with a single parse and merge.
 pub fn both(input: &str) -> Result<(usize, u128), String> {
     let (ranges, ids) = parse_input(input)?;
-    let merged = merge_ranges(ranges);
+    let fresh_ranges = IntervalSet::from_ranges(ranges);
 
     let mut fresh = 0usize;
     for id in ids {
-        fresh += is_fresh(id, &merged) as usize;
+        fresh += fresh_ranges.contains(id) as usize;
+    }
+
+    Ok((fresh, fresh_ranges.count_covered()))
+}
+
+/// A fresh range as written in the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceRange {
+    /// 1-based input line.
+    pub line: usize,
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Why an ingredient ID is (or is not) fresh.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdAudit {
+    pub id: u64,
+    /// Input lines of every original range containing `id`, ascending. Empty
+    /// when the ID is spoiled.
+    pub lines: Vec<usize>,
+}
+
+impl IdAudit {
+    pub fn is_fresh(&self) -> bool {
+        !self.lines.is_empty()
+    }
+}
+
+/// Freshness of every ID, answered with one sorted sweep over the merged
+/// ranges instead of a binary search per ID. Results follow the order of `ids`.
+pub fn batch_fresh(ranges: &IntervalSet<u64>, ids: &[u64]) -> Vec<bool> {
+    let mut order: Vec<usize> = (0..ids.len()).collect();
+    order.sort_unstable_by_key(|&i| ids[i]);
+
+    let merged = ranges.as_slice();
+    let mut fresh = vec![false; ids.len()];
+    let mut r = 0usize;
+    for i in order {
+        let id = ids[i];
+        while r < merged.len() && merged[r].1 < id {
+            r += 1;
+        }
+        if r == merged.len() {
+            break;
+        }
+        fresh[i] = merged[r].0 <= id;
+    }
+    fresh
+}
+
+/// For every ID, list the original (unmerged) ranges that contain it. IDs are
+/// swept in ascending order while a min-heap on range end keeps exactly the
+/// ranges covering the current ID, so the cost is O((n + m) log n) plus the
+/// size of the output. Results follow the order of `ids`.
+pub fn explain_ids(ranges: &[SourceRange], ids: &[u64]) -> Vec<IdAudit> {
+    let mut by_start: Vec<&SourceRange> = ranges.iter().collect();
+    by_start.sort_unstable_by_key(|r| (r.start, r.line));
+    let mut order: Vec<usize> = (0..ids.len()).collect();
+    order.sort_unstable_by_key(|&i| ids[i]);
+
+    let mut audits: Vec<IdAudit> = ids
+        .iter()
+        .map(|&id| IdAudit {
+            id,
+            lines: Vec::new(),
+        })
+        .collect();
+    let mut active: BinaryHeap<Reverse<(u64, usize)>> = BinaryHeap::new();
+    let mut next = 0usize;
+    for i in order {
+        let id = ids[i];
+        while next < by_start.len() && by_start[next].start <= id {
+            active.push(Reverse((by_start[next].end, by_start[next].line)));
+            next += 1;
+        }
+        while active.peek().is_some_and(|Reverse((end, _))| *end < id) {
+            active.pop();
+        }
+        let mut lines: Vec<usize> = active.iter().map(|Reverse((_, line))| *line).collect();
+        lines.sort_unstable();
+        audits[i].lines = lines;
+    }
+    audits
+}
+
+/// Parse the input and explain every listed ingredient ID.
+pub fn audit(input: &str) -> Result<Vec<IdAudit>, String> {
+    let (ranges, ids) = parse_input_with_lines(input)?;
+    Ok(explain_ids(&ranges, &ids))
+}
+
+/// Long-lived fresh-range database. Ranges are kept merged in a `BTreeMap`
+/// (start -> inclusive end), so inserts, removals and lookups cost O(log n)
+/// amortized, and the number of covered IDs is updated as ranges change.
+/// Removing a range subtracts those IDs regardless of which ranges added them.
+#[derive(Clone, Debug, Default)]
+pub struct FreshDb {
+    ranges: BTreeMap<u64, u64>,
+    covered: u128,
+    /// Ingredient IDs from the second section, kept for round-tripping.
+    ids: Vec<u64>,
+}
+
+impl FreshDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load from the puzzle's text format. Either section may be empty.
+    pub fn load(input: &str) -> Result<Self, String> {
+        let mut db = Self::new();
+        db.ids = parse_sections(input, |_, start, end| db.insert(start, end))?;
+        Ok(db)
+    }
+
+    /// Serialize in the puzzle's text format, with ranges merged and sorted.
+    pub fn save(&self) -> String {
+        let mut out = String::new();
+        for (start, end) in self.ranges() {
+            out.push_str(&format!("{}-{}\n", start, end));
+        }
+        out.push('\n');
+        for id in &self.ids {
+            out.push_str(&format!("{}\n", id));
+        }
+        out
+    }
+
+    /// Mark `start..=end` as fresh.
+    pub fn insert(&mut self, start: u64, end: u64) {
+        if start > end {
+            return;
+        }
+        let (mut lo, mut hi) = (start, end);
+        if let Some((&s, &e)) = self.ranges.range(..start).next_back() {
+            if e.checked_add(1).is_none_or(|next| next >= start) {
+                lo = s;
+                hi = hi.max(e);
+                self.take(s);
+            }
+        }
+        while let Some((&s, _)) = self.ranges.range(lo..=hi.saturating_add(1)).next() {
+            hi = hi.max(self.take(s));
+        }
+        self.put(lo, hi);
+    }
+
+    /// Mark `start..=end` as no longer fresh.
+    pub fn remove(&mut self, start: u64, end: u64) {
+        if start > end {
+            return;
+        }
+        if let Some((&s, &e)) = self.ranges.range(..start).next_back() {
+            if e >= start {
+                self.take(s);
+                self.put(s, start - 1);
+                if e > end {
+                    self.put(end + 1, e);
+                }
+            }
+        }
+        while let Some((&s, _)) = self.ranges.range(start..=end).next() {
+            let e = self.take(s);
+            if e > end {
+                self.put(end + 1, e);
+            }
+        }
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.ranges
+            .range(..=id)
+            .next_back()
+            .is_some_and(|(_, &end)| id <= end)
+    }
+
+    /// Number of fresh IDs (size of the union of all ranges).
+    pub fn covered(&self) -> u128 {
+        self.covered
+    }
+
+    /// Merged ranges in ascending order.
+    pub fn ranges(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.ranges.iter().map(|(&s, &e)| (s, e))
+    }
+
+    pub fn ids(&self) -> &[u64] {
+        &self.ids
+    }
+
+    /// Track another ingredient ID (saved in the second section).
+    pub fn push_id(&mut self, id: u64) {
+        self.ids.push(id);
     }
 
-    let mut total: u128 = 0;
-    for (start, end) in merged {
-        total += (end - start + 1) as u128;
+    fn put(&mut self, start: u64, end: u64) {
+        self.covered += (end - start) as u128 + 1;
+        self.ranges.insert(start, end);
     }
 
-    Ok((fresh, total))
+    fn take(&mut self, start: u64) -> u64 {
+        let end = self.ranges.remove(&start).unwrap_or(start);
+        self.covered -= (end - start) as u128 + 1;
+        end
+    }
 }
 
 fn parse_input(input: &str) -> Result<(Vec<(u64, u64)>, Vec<u64>), String> {
-    let bytes = input.as_bytes();
     let mut ranges = Vec::new();
+    let ids = parse_sections(input, |_, start, end| ranges.push((start, end)))?;
+    check_sections(ranges.len(), ids.len())?;
+    Ok((ranges, ids))
+}
+
+/// Like `parse_input`, but keeps the input line of every range.
+fn parse_input_with_lines(input: &str) -> Result<(Vec<SourceRange>, Vec<u64>), String> {
+    let mut ranges = Vec::new();
+    let ids = parse_sections(input, |line_idx, start, end| {
+        ranges.push(SourceRange {
+            line: line_idx + 1,
+            start,
+            end,
+        })
+    })?;
+    check_sections(ranges.len(), ids.len())?;
+    Ok((ranges, ids))
+}
+
+fn check_sections(ranges: usize, ids: usize) -> Result<(), String> {
+    if ranges == 0 {
+        return Err("input contained no ranges".to_string());
+    }
+    if ids == 0 {
+        return Err("input contained no ingredient ids".to_string());
+    }
+    Ok(())
+}
+
+/// Walk both sections, handing each range (with its 0-based line index) to
+/// `on_range` and collecting the ingredient IDs.
+#[inline(always)]
+fn parse_sections(
+    input: &str,
+    mut on_range: impl FnMut(usize, u64, u64),
+) -> Result<Vec<u64>, String> {
+    let bytes = input.as_bytes();
     let mut ids = Vec::new();
     let mut line_start = 0usize;
     let mut line_idx = 0usize;
@@ -79,7 +310,7 @@ fn parse_input(input: &str) -> Result<(Vec<(u64, u64)>, Vec<u64>), String> {
                             line_idx + 1
                         ));
                     }
-                    ranges.push((start, end_num));
+                    on_range(line_idx, start, end_num);
                 } else {
                     let id = parse_number(line, line_idx)?;
                     ids.push(id);
@@ -92,14 +323,7 @@ fn parse_input(input: &str) -> Result<(Vec<(u64, u64)>, Vec<u64>), String> {
         i += 1;
     }
 
-    if ranges.is_empty() {
-        return Err("input contained no ranges".to_string());
-    }
-    if ids.is_empty() {
-        return Err("input contained no ingredient ids".to_string());
-    }
-
-    Ok((ranges, ids))
+    Ok(ids)
 }
 
 fn parse_ranges(input: &str) -> Result<Vec<(u64, u64)>, String> {
@@ -169,42 +393,11 @@ fn parse_number(bytes: &[u8], line_idx: usize) -> Result<u64, String> {
     Ok(value)
 }
 
-fn merge_ranges(mut ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
-    if ranges.len() <= 1 {
-        return ranges;
-    }
-
-    ranges.sort_unstable_by_key(|&(start, _)| start);
-    let mut merged = Vec::with_capacity(ranges.len());
-    let mut current = ranges[0];
-
-    for &(start, end) in ranges.iter().skip(1) {
-        if start <= current.1 + 1 {
-            if end > current.1 {
-                current.1 = end;
-            }
-        } else {
-            merged.push(current);
-            current = (start, end);
-        }
-    }
-    merged.push(current);
-
-    merged
-}
-
-fn is_fresh(id: u64, ranges: &[(u64, u64)]) -> bool {
-    let idx = ranges.partition_point(|&(start, _)| start <= id);
-    if idx == 0 {
-        return false;
-    }
-    let (_, end) = unsafe { *ranges.get_unchecked(idx - 1) };
-    id <= end
-}
-
 #[cfg(test)]
 mod tests {
-    use super::{part1, part2};
+    use super::{audit, batch_fresh, part1, part2, FreshDb};
+    use crate::interval_set::IntervalSet;
+    use crate::test_rng::XorShift;
 
     const EXAMPLE: &str = "\
 3-5
@@ -229,4 +422,73 @@ mod tests {
     fn example_counts_total_fresh_space() {
         assert_eq!(part2(EXAMPLE).unwrap(), 14);
     }
+
+    #[test]
+    fn batch_queries_and_explanations() {
+        let set = IntervalSet::from_ranges([(3, 5), (10, 14), (16, 20), (12, 18)]);
+        assert_eq!(
+            batch_fresh(&set, &[32, 17, 1, 11, 5, 8]),
+            vec![false, true, false, true, true, false]
+        );
+
+        let audits = audit(EXAMPLE).unwrap();
+        let lines: Vec<(u64, Vec<usize>)> =
+            audits.iter().map(|a| (a.id, a.lines.clone())).collect();
+        assert_eq!(
+            lines,
+            vec![
+                (1, vec![]),
+                (5, vec![1]),
+                (8, vec![]),
+                (11, vec![2]),
+                (17, vec![3, 4]),
+                (32, vec![]),
+            ]
+        );
+        assert_eq!(audits.iter().filter(|a| a.is_fresh()).count(), 3);
+    }
+
+    #[test]
+    fn fresh_db_tracks_incremental_updates() {
+        let mut db = FreshDb::load(EXAMPLE).unwrap();
+        assert_eq!(db.covered(), part2(EXAMPLE).unwrap());
+        let fresh = db.ids().iter().filter(|&&id| db.contains(id)).count();
+        assert_eq!(fresh, part1(EXAMPLE).unwrap());
+
+        db.remove(13, 15);
+        db.insert(6, 9);
+        assert_eq!(db.ranges().collect::<Vec<_>>(), vec![(3, 12), (16, 20)]);
+        assert_eq!(db.covered(), 15);
+        assert!(db.contains(9) && !db.contains(14));
+
+        let reloaded = FreshDb::load(&db.save()).unwrap();
+        assert_eq!(
+            reloaded.ranges().collect::<Vec<_>>(),
+            vec![(3, 12), (16, 20)]
+        );
+        assert_eq!(reloaded.ids(), db.ids());
+
+        // Random operations against the array-backed IntervalSet.
+        let mut reference: IntervalSet<u64> = IntervalSet::new();
+        let mut db = FreshDb::new();
+        let mut rng = XorShift::new(0x9e37_79b9_7f4a_7c15);
+        for step in 0..2000 {
+            let state = rng.next_u64();
+            let lo = state % 500;
+            let hi = lo + (state >> 32) % 40;
+            if step % 3 == 0 {
+                db.remove(lo, hi);
+                reference.remove(lo, hi);
+            } else {
+                db.insert(lo, hi);
+                reference.insert(lo, hi);
+            }
+            assert_eq!(db.covered(), reference.count_covered());
+        }
+        assert_eq!(db.ranges().collect::<Vec<_>>(), reference.as_slice());
+        db.insert(u64::MAX - 1, u64::MAX);
+        db.insert(0, 0);
+        db.remove(u64::MAX, u64::MAX);
+        assert!(db.contains(u64::MAX - 1) && !db.contains(u64::MAX) && db.contains(0));
+    }
 }
@@ -12,63 +12,384 @@ pub fn part2(input: &str) -> Result<u128, String> {
     Ok(p2)
 }
 
-/// Compute both parts in a single pass to avoid double parsing.
+/// Compute both parts in a single pass to avoid double parsing. Arithmetic is
+/// checked: a problem whose result leaves the `u128` range is reported.
 pub fn both(input: &str) -> Result<(u128, u128), String> {
+    evaluate::<u128>(input)
+}
+
+/// Worksheet operator. Operands are folded in reading order: top-to-bottom
+/// for part 1 and right-to-left (by column) for part 2. Each problem has a
+/// single operator, so precedence between operators never arises; mixed
+/// expressions within a problem are not supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    /// `+`: sum.
+    Add,
+    /// `*`: product.
+    Mul,
+    /// `-`: first operand minus all the others (left-associative).
+    Sub,
+    /// `/`: truncating division, left-associative; dividing by zero is an error.
+    Div,
+    /// `^`: exponentiation, right-associative (`a ^ b ^ c = a ^ (b ^ c)`).
+    Pow,
+    /// `max`: largest operand.
+    Max,
+    /// `min`: smallest operand.
+    Min,
+}
+
+impl Op {
+    /// The worksheet token for this operator.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Op::Add => "+",
+            Op::Mul => "*",
+            Op::Sub => "-",
+            Op::Div => "/",
+            Op::Pow => "^",
+            Op::Max => "max",
+            Op::Min => "min",
+        }
+    }
+
+    fn parse(token: &[u8]) -> Option<Self> {
+        Some(match token {
+            b"+" => Op::Add,
+            b"*" => Op::Mul,
+            b"-" => Op::Sub,
+            b"/" => Op::Div,
+            b"^" => Op::Pow,
+            b"max" => Op::Max,
+            b"min" => Op::Min,
+            _ => return None,
+        })
+    }
+}
+
+/// Number type a worksheet can be evaluated in. Every operation is checked and
+/// returns `None` when the result does not fit.
+pub trait Number: Clone + Ord + std::fmt::Display {
+    /// Name used in overflow errors.
+    const NAME: &'static str;
+    fn zero() -> Self;
+    /// `self * 10 + digit`.
+    fn push_digit(&self, digit: u8) -> Option<Self>;
+    fn add(&self, other: &Self) -> Option<Self>;
+    fn sub(&self, other: &Self) -> Option<Self>;
+    fn mul(&self, other: &Self) -> Option<Self>;
+    /// Only called with a non-zero divisor.
+    fn div(&self, other: &Self) -> Option<Self>;
+    fn pow(&self, exp: &Self) -> Option<Self>;
+}
+
+macro_rules! impl_number {
+    ($($t:ty),*) => {$(
+        impl Number for $t {
+            const NAME: &'static str = stringify!($t);
+            #[inline]
+            fn zero() -> Self {
+                0
+            }
+            #[inline]
+            fn push_digit(&self, digit: u8) -> Option<Self> {
+                self.checked_mul(10)?.checked_add(digit as $t)
+            }
+            #[inline]
+            fn add(&self, other: &Self) -> Option<Self> {
+                self.checked_add(*other)
+            }
+            #[inline]
+            fn sub(&self, other: &Self) -> Option<Self> {
+                self.checked_sub(*other)
+            }
+            #[inline]
+            fn mul(&self, other: &Self) -> Option<Self> {
+                self.checked_mul(*other)
+            }
+            #[inline]
+            fn div(&self, other: &Self) -> Option<Self> {
+                self.checked_div(*other)
+            }
+            fn pow(&self, exp: &Self) -> Option<Self> {
+                match u32::try_from(*exp) {
+                    Ok(exp) => self.checked_pow(exp),
+                    // Huge exponents only fit for bases 0 and 1.
+                    Err(_) if *self == 0 || *self == 1 => Some(*self),
+                    Err(_) => None,
+                }
+            }
+        }
+    )*};
+}
+
+impl_number!(u128, i128);
+
+/// Largest power, in bits, that [`Number::pow`] computes for `BigInt`. A tower
+/// like `9 ^ 9 ^ 9` would otherwise take minutes and hundreds of megabytes.
+#[cfg(feature = "bigint")]
+pub const MAX_BIGINT_BITS: u64 = 1 << 20;
+
+#[cfg(feature = "bigint")]
+impl Number for num_bigint::BigInt {
+    const NAME: &'static str = "BigInt";
+    fn zero() -> Self {
+        Self::from(0u8)
+    }
+    fn push_digit(&self, digit: u8) -> Option<Self> {
+        Some(self * 10u8 + digit)
+    }
+    fn add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+    fn sub(&self, other: &Self) -> Option<Self> {
+        Some(self - other)
+    }
+    fn mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+    fn div(&self, other: &Self) -> Option<Self> {
+        Some(self / other)
+    }
+    /// Powers whose result would need more than [`MAX_BIGINT_BITS`] bits are
+    /// refused rather than computed.
+    fn pow(&self, exp: &Self) -> Option<Self> {
+        let unit = *self == Self::zero() || *self == Self::from(1u8);
+        match u32::try_from(exp) {
+            Ok(exp) if unit || exp as u64 * self.bits() <= MAX_BIGINT_BITS => {
+                Some(num_bigint::BigInt::pow(self, exp))
+            }
+            Err(_) if unit => Some(self.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Fold of one problem's operands in reading order.
+struct Accumulator<N> {
+    op: Op,
+    value: Option<N>,
+    /// Exponentiation is right-associative, so its operands are folded at the end.
+    pending: Vec<N>,
+}
+
+impl<N: Number> Accumulator<N> {
+    fn new(op: Op) -> Self {
+        Self {
+            op,
+            value: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// `Err(true)` means division by zero, `Err(false)` overflow.
+    #[inline]
+    fn push(&mut self, x: N) -> Result<(), bool> {
+        let Some(acc) = self.value.take() else {
+            self.value = Some(x);
+            return Ok(());
+        };
+        let next = match self.op {
+            Op::Add => acc.add(&x),
+            Op::Mul => acc.mul(&x),
+            Op::Sub => acc.sub(&x),
+            Op::Div if x == N::zero() => return Err(true),
+            Op::Div => acc.div(&x),
+            Op::Max => Some(acc.max(x)),
+            Op::Min => Some(acc.min(x)),
+            Op::Pow => {
+                self.pending.push(acc);
+                Some(x)
+            }
+        };
+        self.value = Some(next.ok_or(false)?);
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Option<N>, bool> {
+        let Some(mut acc) = self.value else {
+            return Ok(None);
+        };
+        while let Some(base) = self.pending.pop() {
+            acc = base.pow(&acc).ok_or(false)?;
+        }
+        Ok(Some(acc))
+    }
+}
+
+/// Evaluate both readings of the worksheet in `N`, supporting every [`Op`].
+/// Errors name the problem (1-based, left to right) and reading order that
+/// overflowed or divided by zero.
+pub fn evaluate<N: Number>(input: &str) -> Result<(N, N), String> {
+    let mut totals = (N::zero(), N::zero());
+    for_each_problem(input, false, |number, problem: Problem<N>| {
+        add_to_totals(&mut totals, number, &problem)
+    })?;
+    Ok(totals)
+}
+
+/// One worksheet problem as parsed, for auditing the segmentation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem<N> {
+    /// Columns of the segment (0-based, end exclusive), as split by blank columns.
+    pub columns: std::ops::Range<usize>,
+    pub op: Op,
+    /// Operands read row by row, top to bottom.
+    pub row_operands: Vec<N>,
+    /// Operands read column by column, right to left, with their column.
+    pub col_operands: Vec<(usize, N)>,
+    pub row_result: N,
+    pub col_result: N,
+}
+
+/// Per-problem view of [`evaluate`]: segment columns, operator, operands in
+/// both reading orders and both results, left to right.
+pub fn breakdown<N: Number>(input: &str) -> Result<Vec<Problem<N>>, String> {
+    let mut problems = Vec::new();
+    for_each_problem(input, true, |_, problem| {
+        problems.push(problem);
+        Ok(())
+    })?;
+    Ok(problems)
+}
+
+/// Sum a [`breakdown`]'s results into both parts' answers, with the same
+/// overflow errors as [`evaluate`].
+pub fn totals<N: Number>(problems: &[Problem<N>]) -> Result<(N, N), String> {
+    let mut totals = (N::zero(), N::zero());
+    for (idx, problem) in problems.iter().enumerate() {
+        add_to_totals(&mut totals, idx + 1, problem)?;
+    }
+    Ok(totals)
+}
+
+fn add_to_totals<N: Number>(
+    (total_row, total_col): &mut (N, N),
+    number: usize,
+    problem: &Problem<N>,
+) -> Result<(), String> {
+    *total_row = total_row.add(&problem.row_result).ok_or_else(|| {
+        format!(
+            "row-wise total overflowed {} at problem {}",
+            N::NAME,
+            number
+        )
+    })?;
+    *total_col = total_col.add(&problem.col_result).ok_or_else(|| {
+        format!(
+            "column-wise total overflowed {} at problem {}",
+            N::NAME,
+            number
+        )
+    })?;
+    Ok(())
+}
+
+/// Parse and solve every problem left to right, passing each to `visit` with
+/// its 1-based number. Operand lists are only filled in when `keep_operands`
+/// is set.
+fn for_each_problem<N: Number>(
+    input: &str,
+    keep_operands: bool,
+    mut visit: impl FnMut(usize, Problem<N>) -> Result<(), String>,
+) -> Result<(), String> {
     let (lines, width) = parse_lines(input)?;
-    let height = lines.len();
     let segments = find_segments(&lines, width)?;
-    let op_row = height - 1;
-    let mut total_row: u128 = 0;
-    let mut total_col: u128 = 0;
+    let op_row = lines.len() - 1;
+    let fail = arithmetic_error::<N>;
 
     for (problem_idx, &(seg_start, seg_end)) in segments.iter().enumerate() {
-        let op = operator_for_segment(&lines[op_row], seg_start, seg_end, problem_idx + 1)?;
-
-        let mut result_row = if op == b'+' { 0 } else { 1 };
-        for row in 0..op_row {
-            let val = parse_row_number(&lines[row], seg_start, seg_end, problem_idx + 1, row + 1)?;
-            if op == b'+' {
-                result_row += val;
-            } else {
-                result_row *= val;
+        let problem = problem_idx + 1;
+        let op = operator_for_segment(&lines[op_row], seg_start, seg_end, problem)?;
+
+        let mut row_operands = Vec::new();
+        let mut acc_row = Accumulator::new(op);
+        for (row, line) in lines[..op_row].iter().enumerate() {
+            let val: N = parse_row_number(line, seg_start, seg_end, problem, row + 1)?;
+            if keep_operands {
+                row_operands.push(val.clone());
             }
+            acc_row
+                .push(val)
+                .map_err(|dz| fail(problem, "row-wise", dz))?;
         }
 
-        let mut result_col = if op == b'+' { 0 } else { 1 };
+        let mut col_operands = Vec::new();
+        let mut acc_col = Accumulator::new(op);
         for col in (seg_start..seg_end).rev() {
-            let mut value: u128 = 0;
-            let mut found = false;
-            for row in 0..op_row {
-                let ch = unsafe { *lines[row].get_unchecked(col) };
-                if ch == b' ' {
-                    continue;
-                }
-                if ch < b'0' || ch > b'9' {
-                    return Err(format!(
-                        "non-digit character '{}' in problem {} at row {} column {}",
-                        ch as char,
-                        problem_idx + 1,
-                        row + 1,
-                        col + 1
-                    ));
-                }
-                value = value * 10 + (ch - b'0') as u128;
-                found = true;
-            }
-            if found {
-                if op == b'+' {
-                    result_col += value;
-                } else {
-                    result_col *= value;
+            if let Some(value) = read_column::<N>(&lines[..op_row], col, problem)? {
+                if keep_operands {
+                    col_operands.push((col, value.clone()));
                 }
+                acc_col
+                    .push(value)
+                    .map_err(|dz| fail(problem, "column-wise", dz))?;
             }
         }
 
-        total_row += result_row;
-        total_col += result_col;
+        let no_operands = || format!("problem {} has no operands", problem);
+        let row_result = acc_row
+            .finish()
+            .map_err(|dz| fail(problem, "row-wise", dz))?
+            .ok_or_else(no_operands)?;
+        let col_result = acc_col
+            .finish()
+            .map_err(|dz| fail(problem, "column-wise", dz))?
+            .ok_or_else(no_operands)?;
+        visit(
+            problem,
+            Problem {
+                columns: seg_start..seg_end,
+                op,
+                row_operands,
+                col_operands,
+                row_result,
+                col_result,
+            },
+        )?;
     }
+    Ok(())
+}
 
-    Ok((total_row, total_col))
+fn arithmetic_error<N: Number>(problem: usize, reading: &str, div_zero: bool) -> String {
+    if div_zero {
+        format!("division by zero in problem {} ({})", problem, reading)
+    } else {
+        format!("problem {} overflowed {} ({})", problem, N::NAME, reading)
+    }
+}
+
+/// Number written top-to-bottom in `col`, or `None` if the column holds only
+/// spaces.
+#[inline]
+fn read_column<N: Number>(
+    rows: &[Vec<u8>],
+    col: usize,
+    problem: usize,
+) -> Result<Option<N>, String> {
+    let mut value = N::zero();
+    let mut found = false;
+    for (row, line) in rows.iter().enumerate() {
+        let ch = unsafe { *line.get_unchecked(col) };
+        if ch == b' ' {
+            continue;
+        }
+        if !ch.is_ascii_digit() {
+            return Err(format!(
+                "non-digit character '{}' in problem {} at row {} column {}",
+                ch as char,
+                problem,
+                row + 1,
+                col + 1
+            ));
+        }
+        value = value
+            .push_digit(ch - b'0')
+            .ok_or_else(|| arithmetic_error::<N>(problem, "column-wise", false))?;
+        found = true;
+    }
+    Ok(found.then_some(value))
 }
 
 fn parse_lines(input: &str) -> Result<(Vec<Vec<u8>>, usize), String> {
@@ -140,32 +461,37 @@ fn operator_for_segment(
     start: usize,
     end: usize,
     problem_idx: usize,
-) -> Result<u8, String> {
-    let mut op = None;
-    for col in start..end {
-        let ch = unsafe { *op_line.get_unchecked(col) };
-        if ch != b' ' {
-            if op.is_some() {
-                return Err(format!(
-                    "multiple operator characters found for problem {}",
-                    problem_idx
-                ));
-            }
-            op = Some(ch);
-        }
+) -> Result<Op, String> {
+    let segment = &op_line[start..end];
+    let lo = segment.iter().position(|&ch| ch != b' ');
+    let Some(lo) = lo else {
+        return Err(format!("missing operator for problem {}", problem_idx));
+    };
+    let hi = segment.iter().rposition(|&ch| ch != b' ').unwrap_or(lo) + 1;
+    let token = &segment[lo..hi];
+    if token.contains(&b' ') {
+        return Err(format!(
+            "multiple operator characters found for problem {}",
+            problem_idx
+        ));
     }
-    let op = op.ok_or_else(|| format!("missing operator for problem {}", problem_idx))?;
-    Ok(op)
+    Op::parse(token).ok_or_else(|| {
+        format!(
+            "unknown operator '{}' for problem {}",
+            String::from_utf8_lossy(token),
+            problem_idx
+        )
+    })
 }
 
 #[inline]
-fn parse_row_number(
+fn parse_row_number<N: Number>(
     line: &[u8],
     start: usize,
     end: usize,
     problem_idx: usize,
     row_idx: usize,
-) -> Result<u128, String> {
+) -> Result<N, String> {
     let mut lo = start;
     while lo < end && unsafe { *line.get_unchecked(lo) } == b' ' {
         lo += 1;
@@ -181,23 +507,25 @@ fn parse_row_number(
         ));
     }
 
-    let mut value: u128 = 0;
+    let mut value = N::zero();
     for idx in lo..hi {
         let b = unsafe { *line.get_unchecked(idx) };
-        if !(b'0'..=b'9').contains(&b) {
+        if !b.is_ascii_digit() {
             return Err(format!(
                 "non-digit character '{}' in problem {} on row {}",
                 b as char, problem_idx, row_idx
             ));
         }
-        value = value * 10 + (b - b'0') as u128;
+        value = value
+            .push_digit(b - b'0')
+            .ok_or_else(|| format!("problem {} overflowed {} (row-wise)", problem_idx, N::NAME))?;
     }
     Ok(value)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{part1, part2};
+    use super::{breakdown, evaluate, part1, part2, totals, Op};
 
     const EXAMPLE: &str = "123 328  51 64 \n 45 64  387 23 \n  6 98  215 314\n*   +   *   +  ";
 
@@ -210,4 +538,70 @@ mod tests {
     fn example_part2() {
         assert_eq!(part2(EXAMPLE).unwrap(), 3_263_827);
     }
+
+    #[test]
+    fn breakdown_lists_each_problem() {
+        let problems = breakdown::<u128>(EXAMPLE).unwrap();
+        assert_eq!(problems.len(), 4);
+        let first = &problems[0];
+        assert_eq!((first.columns.clone(), first.op), (0..3, Op::Mul));
+        assert_eq!(first.row_operands, vec![123, 45, 6]);
+        assert_eq!(first.col_operands, vec![(2, 356), (1, 24), (0, 1)]);
+        assert_eq!((first.row_result, first.col_result), (33_210, 8_544));
+        assert_eq!(totals(&problems).unwrap(), (4_277_556, 3_263_827));
+
+        // Each problem fits, their row-wise sum does not.
+        let max = u128::MAX.to_string();
+        let sheet = format!("{} {}\n+{}+", max, max, " ".repeat(max.len()));
+        let problems = breakdown::<u128>(&sheet).unwrap();
+        let message = "row-wise total overflowed u128 at problem 2";
+        assert_eq!(totals(&problems).unwrap_err(), message);
+        assert_eq!(evaluate::<u128>(&sheet).unwrap_err(), message);
+    }
+
+    #[test]
+    fn extended_operators_and_overflow() {
+        // Row-wise: 20 - 3 - 5, 2 ^ 3 ^ 2, max(7, 12, 9), 50 / 2 / 5.
+        // Column-wise: 35 - 2, 232, max(2, 719), 25 / 5.
+        let sheet = "20 2 7   50\n 3 3 12   2\n 5 2 9    5\n-  ^ max / ";
+        let (row, col) = evaluate::<i128>(sheet).unwrap();
+        assert_eq!(row, 12 + 512 + 12 + 5);
+        assert_eq!(col, 33 + 232 + 719 + 5);
+        assert_eq!(evaluate::<u128>(sheet).unwrap(), (541, 989));
+
+        assert_eq!(
+            evaluate::<u128>("7 3\n1 5\n+ -").unwrap_err(),
+            "problem 2 overflowed u128 (row-wise)"
+        );
+
+        let zero = "8 4\n0 2\n/ /";
+        assert_eq!(
+            evaluate::<i128>(zero).unwrap_err(),
+            "division by zero in problem 1 (row-wise)"
+        );
+
+        let big = format!("{}\n{}\n*", "9".repeat(20), "9".repeat(20));
+        assert!(evaluate::<u128>(&big).is_err());
+        assert!(evaluate::<u128>("1 2\n3 4\n% +")
+            .unwrap_err()
+            .contains("unknown operator"));
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn arbitrary_precision_mode() {
+        use num_bigint::BigInt;
+        let big = format!("{}\n{}\n*", "9".repeat(20), "9".repeat(20));
+        let (row, _) = evaluate::<BigInt>(&big).unwrap();
+        let n: BigInt = "9".repeat(20).parse().unwrap();
+        assert_eq!(row, &n * &n);
+
+        // 9 ^ (9 ^ 9) has about 370 million bits.
+        assert_eq!(
+            evaluate::<BigInt>("9\n9\n9\n^").unwrap_err(),
+            "problem 1 overflowed BigInt (row-wise)"
+        );
+        let (row, col) = evaluate::<BigInt>("2\n3\n3\n^").unwrap();
+        assert_eq!((row, col), (BigInt::from(1u32 << 27), BigInt::from(233u32)));
+    }
 }
@@ -18,8 +18,120 @@ pub fn both(input: &str) -> Result<(u128, u128), String> {
     Ok((splits, timelines))
 }
 
+/// How a beam moving down reacts to the cell it enters:
+///
+/// - `.` and `S`: the beam continues straight down.
+/// - `^`: splitter; the beam continues one column to the left and one to the right.
+/// - `*`: three-way splitter; left, straight down and right.
+/// - `/` and `\`: mirrors; the beam is deflected diagonally and continues
+///   one column to the left (`/`) or right (`\`) without splitting.
+/// - `#`: absorber; the beam stops and its timeline ends there.
+///
+/// A beam sent past the left or right edge leaves the manifold, which also ends
+/// its timeline. Part 1 counts the splitters (`^` and `*`) hit by any beam; part 2
+/// counts timelines, i.e. the distinct beam paths.
+#[derive(Clone, Copy)]
+enum Cell {
+    Pass,
+    Split(&'static [Turn]),
+    Deflect(Turn),
+    Absorb,
+}
+
+impl Cell {
+    #[inline]
+    fn from_byte(ch: u8) -> Option<Self> {
+        Some(match ch {
+            b'.' | b'S' => Cell::Pass,
+            b'^' => Cell::Split(&[Turn::Left, Turn::Right]),
+            b'*' => Cell::Split(&[Turn::Left, Turn::Straight, Turn::Right]),
+            b'/' => Cell::Deflect(Turn::Left),
+            b'\\' => Cell::Deflect(Turn::Right),
+            b'#' => Cell::Absorb,
+            _ => return None,
+        })
+    }
+}
+
+impl Turn {
+    /// Column after taking this turn from `col`, or `None` off the side.
+    #[inline]
+    fn step(self, col: usize, width: usize) -> Option<usize> {
+        match self {
+            Turn::Left => col.checked_sub(1),
+            Turn::Straight => Some(col),
+            Turn::Right => Some(col + 1).filter(|&next| next < width),
+        }
+    }
+}
+
+/// Timeline counter type. Additions are checked so deep manifolds report an
+/// overflow instead of wrapping.
+pub trait Count: Clone {
+    /// Name used in overflow errors.
+    const NAME: &'static str;
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn is_zero(&self) -> bool;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+}
+
+macro_rules! impl_count {
+    ($($t:ty),*) => {$(
+        impl Count for $t {
+            const NAME: &'static str = stringify!($t);
+            #[inline]
+            fn zero() -> Self {
+                0
+            }
+            #[inline]
+            fn one() -> Self {
+                1
+            }
+            #[inline]
+            fn is_zero(&self) -> bool {
+                *self == 0
+            }
+            #[inline]
+            fn checked_add(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *other)
+            }
+        }
+    )*};
+}
+
+impl_count!(u64, u128);
+
+#[cfg(feature = "bigint")]
+impl Count for num_bigint::BigUint {
+    const NAME: &'static str = "BigUint";
+    fn zero() -> Self {
+        Self::from(0u8)
+    }
+    fn one() -> Self {
+        Self::from(1u8)
+    }
+    fn is_zero(&self) -> bool {
+        self.bits() == 0
+    }
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+}
+
 fn simulate(input: &str) -> Result<(u128, u128), String> {
     let (rows, start_row, start_col) = parse_grid(input)?;
+    if let Some(answers) = simulate_plain(&rows, start_row, start_col) {
+        return Ok(answers);
+    }
+    // Extended cells, or a count past `u128`: the checked sweep names the row.
+    run(&rows, start_row, start_col, |_, _: &[u128]| {})
+}
+
+/// Sweep for grids of only `.`, `^` and `S`, or `None` if a beam meets any
+/// other cell or the timeline count overflows `u128`. No cell can hold more
+/// beams than there are timelines, so only that count needs checking.
+fn simulate_plain(rows: &[&[u8]], start_row: usize, start_col: usize) -> Option<(u128, u128)> {
     let width = rows[0].len();
     let mut current_counts = vec![0u128; width];
     let mut next_counts = vec![0u128; width];
@@ -34,22 +146,90 @@ fn simulate(input: &str) -> Result<(u128, u128), String> {
             if count == 0 {
                 continue;
             }
-            let ch = unsafe { *line.get_unchecked(col) };
-            if ch == b'^' {
-                splitters_hit += 1;
-                timelines += count;
-                if col > 0 {
-                    unsafe {
-                        *next_counts.get_unchecked_mut(col - 1) += count;
+            match unsafe { *line.get_unchecked(col) } {
+                b'^' => {
+                    splitters_hit += 1;
+                    timelines = timelines.checked_add(count)?;
+                    if col > 0 {
+                        let slot = unsafe { next_counts.get_unchecked_mut(col - 1) };
+                        *slot = slot.wrapping_add(count);
+                    }
+                    if col + 1 < width {
+                        let slot = unsafe { next_counts.get_unchecked_mut(col + 1) };
+                        *slot = slot.wrapping_add(count);
                     }
                 }
-                if col + 1 < width {
-                    unsafe {
-                        *next_counts.get_unchecked_mut(col + 1) += count;
+                b'.' | b'S' => {
+                    let slot = unsafe { next_counts.get_unchecked_mut(col) };
+                    *slot = slot.wrapping_add(count);
+                }
+                _ => return None,
+            }
+        }
+        std::mem::swap(&mut current_counts, &mut next_counts);
+    }
+
+    Some((splitters_hit, timelines))
+}
+
+/// Simulate the manifold counting timelines in `C`. Returns the number of
+/// splitters hit and the number of timelines, or an error naming the row where
+/// the count overflowed.
+pub fn simulate_with<C: Count>(input: &str) -> Result<(u128, C), String> {
+    let (rows, start_row, start_col) = parse_grid(input)?;
+    run(&rows, start_row, start_col, |_, _: &[C]| {})
+}
+
+/// Shared row-by-row sweep. `on_row(row, counts)` sees the beams entering
+/// every row below the start.
+fn run<C: Count>(
+    rows: &[&[u8]],
+    start_row: usize,
+    start_col: usize,
+    mut on_row: impl FnMut(usize, &[C]),
+) -> Result<(u128, C), String> {
+    let width = rows[0].len();
+    let mut current_counts = vec![C::zero(); width];
+    let mut next_counts = vec![C::zero(); width];
+    current_counts[start_col] = C::one();
+    let mut splitters_hit: u128 = 0;
+    let mut timelines = C::one();
+
+    for (row_idx, line) in rows.iter().enumerate().skip(start_row + 1) {
+        let overflow = || {
+            format!(
+                "timeline count overflowed {} at row {}",
+                C::NAME,
+                row_idx + 1
+            )
+        };
+        on_row(row_idx, &current_counts);
+        next_counts.fill(C::zero());
+        for (col, count) in current_counts.iter().enumerate() {
+            if count.is_zero() {
+                continue;
+            }
+            let mut send = |turn: Turn| -> Result<(), String> {
+                if let Some(next) = turn.step(col, width) {
+                    let slot = unsafe { next_counts.get_unchecked_mut(next) };
+                    *slot = slot.checked_add(count).ok_or_else(overflow)?;
+                }
+                Ok(())
+            };
+            // Cells were validated while parsing.
+            match Cell::from_byte(unsafe { *line.get_unchecked(col) }) {
+                Some(Cell::Split(turns)) => {
+                    splitters_hit += 1;
+                    for &turn in turns {
+                        send(turn)?;
+                    }
+                    for _ in 1..turns.len() {
+                        timelines = timelines.checked_add(count).ok_or_else(overflow)?;
                     }
                 }
-            } else {
-                unsafe { *next_counts.get_unchecked_mut(col) += count };
+                Some(Cell::Deflect(turn)) => send(turn)?,
+                Some(Cell::Absorb) => {}
+                _ => send(Turn::Straight)?,
             }
         }
         std::mem::swap(&mut current_counts, &mut next_counts);
@@ -58,6 +238,235 @@ fn simulate(input: &str) -> Result<(u128, u128), String> {
     Ok((splitters_hit, timelines))
 }
 
+/// Number of beams entering every cell, for visualising where timelines
+/// multiply.
+pub struct BeamDensity {
+    pub width: usize,
+    pub height: usize,
+    rows: Vec<Vec<u8>>,
+    /// Row-major beam counts; rows above the start are zero.
+    counts: Vec<u128>,
+}
+
+impl BeamDensity {
+    #[inline]
+    pub fn count(&self, row: usize, col: usize) -> u128 {
+        self.counts[row * self.width + col]
+    }
+
+    /// The grid character at `(row, col)`.
+    #[inline]
+    pub fn cell(&self, row: usize, col: usize) -> u8 {
+        self.rows[row][col]
+    }
+
+    pub fn max_count(&self) -> u128 {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+}
+
+/// Run the simulation keeping every row of beam counts.
+pub fn beam_density(input: &str) -> Result<BeamDensity, String> {
+    let (rows, start_row, start_col) = parse_grid(input)?;
+    let (width, height) = (rows[0].len(), rows.len());
+    let mut counts = vec![0u128; width * height];
+    counts[start_row * width + start_col] = 1;
+    run::<u128>(&rows, start_row, start_col, |row, current| {
+        counts[row * width..(row + 1) * width].copy_from_slice(current);
+    })?;
+    Ok(BeamDensity {
+        width,
+        height,
+        rows: rows.into_iter().map(|row| row.to_vec()).collect(),
+        counts,
+    })
+}
+
+/// Which way a beam goes when it leaves a splitter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Turn {
+    Left,
+    /// Only three-way splitters (`*`) let a beam continue straight down.
+    Straight,
+    Right,
+}
+
+/// One splitter on a timeline's path and the branch taken there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Split {
+    pub row: usize,
+    pub col: usize,
+    pub turn: Turn,
+}
+
+/// Where a timeline's beam ends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+    /// Left the bottom row at this column.
+    Bottom(usize),
+    /// Sent past the left or right edge by the cell at `(row, col)`.
+    Side { row: usize, col: usize },
+    /// Stopped by the absorber at `(row, col)`.
+    Absorbed { row: usize, col: usize },
+}
+
+/// A single timeline: the splits it passes through, top to bottom.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Timeline {
+    pub splits: Vec<Split>,
+    pub exit: Exit,
+}
+
+/// Parsed manifold with the number of timelines reachable from every cell, so
+/// individual timelines can be ranked, enumerated and sampled.
+///
+/// Timelines are ranked lexicographically by their turns with `Left` before
+/// `Straight` before `Right`: index 0 always goes left, the last index always
+/// goes right.
+pub struct Manifold {
+    rows: Vec<Vec<u8>>,
+    width: usize,
+    start_row: usize,
+    start_col: usize,
+    /// `ways[row * width + col]`: timelines for a beam entering `row` at `col`;
+    /// row `rows.len()` is all ones.
+    ways: Vec<u128>,
+}
+
+impl Manifold {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let (rows, start_row, start_col) = parse_grid(input)?;
+        let rows: Vec<Vec<u8>> = rows.into_iter().map(|row| row.to_vec()).collect();
+        let width = rows[0].len();
+        let height = rows.len();
+        let mut ways = vec![0u128; (height + 1) * width];
+        ways[height * width..].fill(1);
+        for row in (start_row + 1..height).rev() {
+            let (here, below) = ways[row * width..(row + 2) * width].split_at_mut(width);
+            let ways_after = |turn: Turn, col: usize| turn.step(col, width).map_or(1, |c| below[c]);
+            for (col, cell) in here.iter_mut().enumerate() {
+                *cell = match Cell::from_byte(rows[row][col]) {
+                    Some(Cell::Split(turns)) => turns.iter().try_fold(0u128, |sum, &turn| {
+                        sum.checked_add(ways_after(turn, col)).ok_or_else(|| {
+                            format!(
+                                "timeline count overflowed u128 at row {}, col {}",
+                                row + 1,
+                                col + 1
+                            )
+                        })
+                    })?,
+                    Some(Cell::Deflect(turn)) => ways_after(turn, col),
+                    Some(Cell::Absorb) => 1,
+                    _ => below[col],
+                };
+            }
+        }
+        Ok(Self {
+            rows,
+            width,
+            start_row,
+            start_col,
+            ways,
+        })
+    }
+
+    /// Total number of timelines (the part 2 answer).
+    pub fn timeline_count(&self) -> u128 {
+        self.ways_at(self.start_row + 1, self.start_col)
+    }
+
+    #[inline]
+    fn ways_at(&self, row: usize, col: usize) -> u128 {
+        self.ways[row * self.width + col]
+    }
+
+    /// The timeline with the given rank, or `None` if `index` is out of range.
+    pub fn timeline(&self, index: u128) -> Option<Timeline> {
+        if index >= self.timeline_count() {
+            return None;
+        }
+        let mut rest = index;
+        let mut splits = Vec::new();
+        let mut col = self.start_col;
+        for row in self.start_row + 1..self.rows.len() {
+            let turn = match Cell::from_byte(self.rows[row][col]) {
+                Some(Cell::Split(turns)) => {
+                    let mut chosen = turns[turns.len() - 1];
+                    for &turn in turns {
+                        let ways = turn
+                            .step(col, self.width)
+                            .map_or(1, |c| self.ways_at(row + 1, c));
+                        if rest < ways {
+                            chosen = turn;
+                            break;
+                        }
+                        rest -= ways;
+                    }
+                    splits.push(Split {
+                        row,
+                        col,
+                        turn: chosen,
+                    });
+                    chosen
+                }
+                Some(Cell::Deflect(turn)) => turn,
+                Some(Cell::Absorb) => {
+                    return Some(Timeline {
+                        splits,
+                        exit: Exit::Absorbed { row, col },
+                    })
+                }
+                _ => Turn::Straight,
+            };
+            match turn.step(col, self.width) {
+                Some(next) => col = next,
+                None => {
+                    return Some(Timeline {
+                        splits,
+                        exit: Exit::Side { row, col },
+                    })
+                }
+            }
+        }
+        Some(Timeline {
+            splits,
+            exit: Exit::Bottom(col),
+        })
+    }
+
+    /// All timelines in rank order; use `take(n)` for the first `n`.
+    pub fn timelines(&self) -> impl Iterator<Item = Timeline> + '_ {
+        (0..self.timeline_count()).map_while(|index| self.timeline(index))
+    }
+
+    /// A uniformly random timeline. The same `seed` always gives the same one.
+    pub fn sample(&self, seed: u64) -> Timeline {
+        let total = self.timeline_count();
+        let mut state = seed;
+        let mut next = || {
+            // SplitMix64.
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+        // Rejection sampling below the next power of two keeps the draw unbiased.
+        let bits = 128 - (total - 1).leading_zeros();
+        let mask = if bits == 128 {
+            u128::MAX
+        } else {
+            (1u128 << bits) - 1
+        };
+        loop {
+            let draw = (((next() as u128) << 64) | next() as u128) & mask;
+            if let Some(timeline) = self.timeline(draw) {
+                return timeline;
+            }
+        }
+    }
+}
+
 fn parse_grid(input: &str) -> Result<(Vec<&[u8]>, usize, usize), String> {
     let mut rows: Vec<&[u8]> = Vec::new();
     let mut start_row = None;
@@ -86,6 +495,22 @@ fn parse_grid(input: &str) -> Result<(Vec<&[u8]>, usize, usize), String> {
             expected_width = Some(bytes.len());
         }
 
+        // Most rows are only `.` and `^`; this check vectorises where decoding
+        // each cell does not.
+        let plain = bytes
+            .iter()
+            .fold(true, |plain, &b| plain & (b == b'.' || b == b'^'));
+        if !plain {
+            if let Some(pos) = bytes.iter().position(|&b| Cell::from_byte(b).is_none()) {
+                return Err(format!(
+                    "unknown cell '{}' at row {}, col {}",
+                    bytes[pos] as char,
+                    row_idx + 1,
+                    pos + 1
+                ));
+            }
+        }
+
         if let Some(pos) = bytes.iter().position(|&b| b == b'S') {
             if start_row.is_some() {
                 return Err(format!(
@@ -107,7 +532,7 @@ fn parse_grid(input: &str) -> Result<(Vec<&[u8]>, usize, usize), String> {
 
 #[cfg(test)]
 mod tests {
-    use super::{part1, part2};
+    use super::{beam_density, both, part1, part2, simulate_with, Exit, Manifold, Turn};
 
     const EXAMPLE: &str = ".......S.......\n\
 ...............\n\
@@ -135,4 +560,94 @@ mod tests {
     fn example_part2() {
         assert_eq!(part2(EXAMPLE).unwrap(), 40);
     }
+
+    #[test]
+    fn timelines_are_ranked_and_sampled() {
+        let manifold = Manifold::parse(EXAMPLE).unwrap();
+        assert_eq!(manifold.timeline_count(), 40);
+
+        let all: Vec<_> = manifold.timelines().collect();
+        assert_eq!(all.len(), 40);
+        assert!(all[0].splits.iter().all(|s| s.turn == Turn::Left));
+        assert!(all[39].splits.iter().all(|s| s.turn == Turn::Right));
+        assert_eq!(
+            all[0]
+                .splits
+                .iter()
+                .map(|s| (s.row, s.col))
+                .collect::<Vec<_>>(),
+            vec![(2, 7), (4, 6), (6, 5), (8, 4), (10, 3), (12, 2), (14, 1)]
+        );
+        assert_eq!(all[0].exit, Exit::Bottom(0));
+        for pair in all.windows(2) {
+            let turns = |t: &super::Timeline| {
+                t.splits
+                    .iter()
+                    .map(|s| s.turn == Turn::Right)
+                    .collect::<Vec<_>>()
+            };
+            assert!(turns(&pair[0]) < turns(&pair[1]));
+        }
+        assert!(manifold.timeline(40).is_none());
+
+        let sampled = manifold.sample(7);
+        assert!(all.contains(&sampled));
+        assert_eq!(sampled, manifold.sample(7));
+    }
+
+    #[test]
+    fn extended_cells_and_checked_counts() {
+        // `/` deflects the beam onto the three-way splitter; its left branch is
+        // absorbed, the straight one passes and the right one meets a `^`.
+        let grid = "..S..\n.....\n../..\n.*...\n#.^..\n.....\n";
+        assert_eq!(simulate_with::<u128>(grid).unwrap(), (2, 4));
+        let manifold = Manifold::parse(grid).unwrap();
+        let exits: Vec<_> = manifold.timelines().map(|t| t.exit).collect();
+        assert_eq!(
+            exits,
+            vec![
+                Exit::Absorbed { row: 4, col: 0 },
+                Exit::Bottom(1),
+                Exit::Bottom(1),
+                Exit::Bottom(3),
+            ]
+        );
+        assert!(Manifold::parse("S\n?\n").is_err());
+
+        let density = beam_density(grid).unwrap();
+        assert_eq!(density.count(0, 2), 1);
+        assert_eq!(density.count(3, 1), 1);
+        assert_eq!(
+            density.count(4, 0) + density.count(4, 1) + density.count(4, 2),
+            3
+        );
+        assert_eq!((density.count(5, 1), density.count(5, 3)), (2, 1));
+        assert_eq!(density.max_count(), 2);
+
+        let mut tall = format!("{}S{}\n", ".".repeat(60), ".".repeat(60));
+        for _ in 0..60 {
+            tall.push_str(&"*".repeat(121));
+            tall.push('\n');
+        }
+        let err = simulate_with::<u64>(&tall).unwrap_err();
+        assert!(err.starts_with("timeline count overflowed u64"), "{}", err);
+        let (_, exact) = simulate_with::<u128>(&tall).unwrap();
+        assert_eq!(exact, Manifold::parse(&tall).unwrap().timeline_count());
+        #[cfg(feature = "bigint")]
+        {
+            let (_, big) = simulate_with::<num_bigint::BigUint>(&tall).unwrap();
+            assert_eq!(big, num_bigint::BigUint::from(exact));
+        }
+
+        // `both` falls back from the plain-grid sweep on extended cells and on
+        // overflow.
+        assert_eq!(both(grid).unwrap(), (2, 4));
+        let mut deep = format!("{}S{}\n", ".".repeat(150), ".".repeat(150));
+        for _ in 0..140 {
+            deep.push_str(&"^".repeat(301));
+            deep.push('\n');
+        }
+        let err = both(&deep).unwrap_err();
+        assert!(err.starts_with("timeline count overflowed u128"), "{}", err);
+    }
 }
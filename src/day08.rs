@@ -15,14 +15,28 @@ pub fn both(input: &str) -> Result<(u64, u64), String> {
     solve_with_limit(input, 1000)
 }
 
+use crate::disjoint_set::DisjointSet;
 use std::cmp::Ordering;
-use std::collections::BinaryHeap;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 struct Edge {
     dist: u64,
-    a: u16,
-    b: u16,
+    a: u32,
+    b: u32,
+}
+
+impl Edge {
+    const NONE: Edge = Edge {
+        dist: u64::MAX,
+        a: u32::MAX,
+        b: u32::MAX,
+    };
+
+    #[inline(always)]
+    fn new(dist: u64, i: u32, j: u32) -> Self {
+        let (a, b) = if i < j { (i, j) } else { (j, i) };
+        Edge { dist, a, b }
+    }
 }
 
 impl Ord for Edge {
@@ -40,161 +54,844 @@ impl PartialOrd for Edge {
     }
 }
 
-fn solve_with_limit(input: &str, pair_limit: usize) -> Result<(u64, u64), String> {
+/// Both parts with part 1 connecting the `pair_limit` closest pairs instead of
+/// the puzzle's 1000.
+pub fn solve_with_limit(input: &str, pair_limit: usize) -> Result<(u64, u64), String> {
+    solve_with(input, pair_limit, Metric::Euclidean)
+}
+
+/// Both parts for points of any dimension (every line needs the same number of
+/// comma-separated coordinates) under `metric`. Part 2 multiplies the first
+/// coordinates of the final pair.
+pub fn solve_with(input: &str, pair_limit: usize, metric: Metric) -> Result<(u64, u64), String> {
     let points = parse_points(input)?;
+    let tree = build_tree(&points, metric)?;
+    // Edges are ordered by (distance, a, b), so "the K closest pairs" and "the
+    // last edge Kruskal adds" are well defined even with ties. Part 1 pulls the
+    // first K pairs from a lazy stream; part 2 is the heaviest edge of the
+    // minimum spanning tree, which is exactly Kruskal's final edge.
+    let set = connect(&tree, pair_limit);
+    let last = last_connection(&tree)?;
+    Ok(answers(&points, &set, last))
+}
+
+/// Part 1's product of the three largest circuits (zero if there are fewer
+/// than three) and part 2's product of the final pair's first coordinates.
+fn answers(points: &Points, set: &DisjointSet, last: Edge) -> (u64, u64) {
+    let top = set.top_sizes(3);
+    let p1 = if top.len() == 3 {
+        top.iter().map(|&s| s as u64).product()
+    } else {
+        0
+    };
+    let ax = points.get(last.a as usize)[0];
+    let bx = points.get(last.b as usize)[0];
+    (p1, (ax * bx) as u64)
+}
+
+/// Distance between junction boxes. Every metric yields an exact integer, so
+/// ties are broken the same way whichever is chosen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Metric {
+    /// Straight-line distance, compared (and reported by [`Metric::distance`])
+    /// squared.
+    #[default]
+    Euclidean,
+    /// Sum of per-axis differences.
+    Manhattan,
+    /// Largest per-axis difference.
+    Chebyshev,
+}
+
+impl Metric {
+    /// Distance between `a` and `b`; squared for [`Metric::Euclidean`].
+    #[inline(always)]
+    pub fn distance(self, a: &[i64], b: &[i64]) -> u64 {
+        // The puzzle's 3D points get a fixed-size, fully unrolled loop.
+        if let (Ok(a), Ok(b)) = (<&[i64; 3]>::try_from(a), <&[i64; 3]>::try_from(b)) {
+            return self.distance_fixed(a, b);
+        }
+        let diffs = a.iter().zip(b).map(|(x, y)| x - y);
+        match self {
+            Metric::Euclidean => diffs.map(|d| (d * d) as u64).sum(),
+            Metric::Manhattan => diffs.map(|d| d.unsigned_abs()).sum(),
+            Metric::Chebyshev => diffs.map(|d| d.unsigned_abs()).max().unwrap_or(0),
+        }
+    }
+
+    #[inline(always)]
+    fn distance_fixed<const D: usize>(self, a: &[i64; D], b: &[i64; D]) -> u64 {
+        let mut total = 0u64;
+        for axis in 0..D {
+            let d = a[axis] - b[axis];
+            total = match self {
+                Metric::Euclidean => total + (d * d) as u64,
+                Metric::Manhattan => total + d.unsigned_abs(),
+                Metric::Chebyshev => total.max(d.unsigned_abs()),
+            };
+        }
+        total
+    }
+
+    /// Convert a [`Metric::distance`] value to actual length.
+    pub fn length(self, distance: u64) -> f64 {
+        match self {
+            Metric::Euclidean => (distance as f64).sqrt(),
+            Metric::Manhattan | Metric::Chebyshev => distance as f64,
+        }
+    }
+
+    /// Lower bound on the distance between points `diff` apart along one axis.
+    #[inline(always)]
+    fn axis(self, diff: i64) -> u64 {
+        match self {
+            Metric::Euclidean => (diff * diff) as u64,
+            Metric::Manhattan | Metric::Chebyshev => diff.unsigned_abs(),
+        }
+    }
+
+    /// Fold one axis' gap into a distance accumulated over the other axes.
+    #[inline(always)]
+    fn accumulate(self, total: u64, diff: i64) -> u64 {
+        match self {
+            Metric::Euclidean | Metric::Manhattan => total + self.axis(diff),
+            Metric::Chebyshev => total.max(self.axis(diff)),
+        }
+    }
+
+    /// Lower bound to a cell after its gap along `axis` changed from `old` to
+    /// `off[axis]`, given the previous bound `bound`.
+    #[inline(always)]
+    fn rebound(self, bound: u64, old: i64, off: &[i64], axis: usize) -> u64 {
+        match self {
+            Metric::Euclidean | Metric::Manhattan => bound - self.axis(old) + self.axis(off[axis]),
+            Metric::Chebyshev => off.iter().map(|d| d.unsigned_abs()).max().unwrap_or(0),
+        }
+    }
+}
+
+/// Circuits after a number of connections, plus when everything first joins up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitReport {
+    /// Connections actually made (the limit, capped at the number of pairs).
+    pub pairs: usize,
+    /// Member indices (input line order) of every circuit, largest circuit
+    /// first; equal sizes are ordered by their smallest member.
+    pub circuits: Vec<Vec<usize>>,
+    /// 1-based index, in closest-first order, of the connection that first
+    /// leaves a single circuit.
+    pub connected_at: u64,
+    /// The two junction boxes joined by that connection.
+    pub final_pair: (usize, usize),
+    /// Part 1 and part 2 for this pair limit, as [`solve_with`] returns them.
+    pub answers: (u64, u64),
+}
+
+impl CircuitReport {
+    /// Circuit sizes, largest first.
+    pub fn sizes(&self) -> Vec<usize> {
+        self.circuits.iter().map(Vec::len).collect()
+    }
+}
+
+/// Connect the `pair_limit` closest pairs under `metric` and report the full
+/// circuit decomposition along with both answers. `connected_at` is the final
+/// connection's rank among all pairs, counted per point with whole k-d tree
+/// cells that lie entirely closer than it.
+pub fn circuits(input: &str, pair_limit: usize, metric: Metric) -> Result<CircuitReport, String> {
+    let points = parse_points(input)?;
+    let tree = build_tree(&points, metric)?;
+    let n = points.len();
+    let total_pairs = n as u64 * (n as u64 - 1) / 2;
+    let mut set = connect(&tree, pair_limit);
+    let last = last_connection(&tree)?;
+    let answers = answers(&points, &set, last);
+
+    let boxes = tree.boxes();
+    let mut ordered = 0u64;
+    for slot in 0..n {
+        let mut count = PairCount {
+            tree: &tree,
+            boxes: &boxes,
+            slot,
+            last,
+            count: 0,
+        };
+        count.visit(0, n);
+        ordered += count.count;
+    }
+
+    Ok(CircuitReport {
+        pairs: (pair_limit as u64).min(total_pairs) as usize,
+        circuits: set.groups(),
+        connected_at: ordered / 2,
+        final_pair: (last.a as usize, last.b as usize),
+        answers,
+    })
+}
+
+/// One step of single-linkage clustering.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Merge {
+    /// Clusters joined: `0..n` are the junction boxes, `n + i` is the cluster
+    /// formed by merge `i`.
+    pub left: usize,
+    pub right: usize,
+    /// The closest pair across the two clusters (junction box indices).
+    pub a: usize,
+    pub b: usize,
+    /// [`Metric::distance`] between `a` and `b`.
+    pub dist: u64,
+    /// Junction boxes in the merged cluster.
+    pub size: usize,
+}
+
+/// Single-linkage dendrogram: the minimum spanning tree's edges in merge order.
+///
+/// Merges are not the same as part 1's connections: a connection between boxes
+/// already in one circuit merges nothing, so cutting after `K` merges is not
+/// the state after `K` connections.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dendrogram {
+    leaves: usize,
+    metric: Metric,
+    merges: Vec<Merge>,
+}
+
+/// Build the dendrogram of the junction boxes under `metric`.
+pub fn dendrogram(input: &str, metric: Metric) -> Result<Dendrogram, String> {
+    let points = parse_points(input)?;
+    let tree = build_tree(&points, metric)?;
+    let mut edges = spanning_tree(&tree);
+    edges.sort_unstable();
+
+    let n = points.len();
+    let mut set: DisjointSet = DisjointSet::new(n);
+    // Cluster id currently represented by each union-find root.
+    let mut cluster: Vec<usize> = (0..n).collect();
+    let mut merges = Vec::with_capacity(edges.len());
+    for edge in edges {
+        let (a, b) = (edge.a as usize, edge.b as usize);
+        let left = cluster[set.find(a)];
+        let right = cluster[set.find(b)];
+        set.union(a, b);
+        let root = set.find(a);
+        cluster[root] = n + merges.len();
+        merges.push(Merge {
+            left,
+            right,
+            a,
+            b,
+            dist: edge.dist,
+            size: set.size_of(root),
+        });
+    }
+    Ok(Dendrogram {
+        leaves: n,
+        metric,
+        merges,
+    })
+}
+
+impl Dendrogram {
+    /// Number of junction boxes.
+    pub fn leaves(&self) -> usize {
+        self.leaves
+    }
+
+    /// Merges in order of increasing distance; the last one is part 2's.
+    pub fn merges(&self) -> &[Merge] {
+        &self.merges
+    }
+
+    /// Actual length of a merge's link, used as its height in the exports.
+    pub fn height(&self, merge: &Merge) -> f64 {
+        self.metric.length(merge.dist)
+    }
+
+    /// Clusters when `count` of them remain (clamped to `1..=leaves`), in the
+    /// same order as [`CircuitReport::circuits`].
+    pub fn cut_by_count(&self, count: usize) -> Vec<Vec<usize>> {
+        let count = count.clamp(1, self.leaves);
+        self.clusters_after(self.leaves - count)
+    }
+
+    /// Clusters whose boxes are linked by chains of pairs at most `max_dist`
+    /// apart, in [`Metric::distance`] units.
+    pub fn cut_by_distance(&self, max_dist: u64) -> Vec<Vec<usize>> {
+        let applied = self.merges.partition_point(|m| m.dist <= max_dist);
+        self.clusters_after(applied)
+    }
+
+    fn clusters_after(&self, applied: usize) -> Vec<Vec<usize>> {
+        let mut set: DisjointSet = DisjointSet::new(self.leaves);
+        for merge in &self.merges[..applied] {
+            set.union(merge.a, merge.b);
+        }
+        set.groups()
+    }
+
+    /// Newick tree with leaves labelled by junction box index and branch
+    /// lengths in the metric's units, e.g. `((0:1,1:1):2,2:3);`.
+    pub fn to_newick(&self) -> String {
+        // There are always at least two boxes, hence at least one merge.
+        let root = self.leaves + self.merges.len() - 1;
+        let height = |cluster: usize| match cluster.checked_sub(self.leaves) {
+            Some(merge) => self.height(&self.merges[merge]),
+            None => 0.0,
+        };
+        // Iterative walk: deep chains would overflow the stack when recursing.
+        enum Step {
+            /// Cluster and the length of the branch above it (NaN at the root).
+            Open(usize, f64),
+            Text(&'static str),
+            Close(f64),
+        }
+        let mut out = String::new();
+        let mut stack = vec![Step::Text(";"), Step::Open(root, f64::NAN)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Text(text) => out.push_str(text),
+                Step::Open(cluster, length) if cluster < self.leaves => {
+                    out.push_str(&format!("{}:{}", cluster, length));
+                }
+                Step::Open(cluster, length) => {
+                    let merge = &self.merges[cluster - self.leaves];
+                    let own = self.height(merge);
+                    out.push('(');
+                    stack.push(Step::Close(length));
+                    stack.push(Step::Open(merge.right, own - height(merge.right)));
+                    stack.push(Step::Text(","));
+                    stack.push(Step::Open(merge.left, own - height(merge.left)));
+                }
+                Step::Close(length) => {
+                    out.push(')');
+                    if !length.is_nan() {
+                        out.push_str(&format!(":{}", length));
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// JSON linkage list: `{"leaves": n, "merges": [{"left", "right", "a",
+    /// "b", "distance", "size"}, ...]}` with cluster ids as in [`Merge`].
+    pub fn to_json(&self) -> String {
+        let mut out = format!("{{\"leaves\":{},\"merges\":[", self.leaves);
+        for (idx, merge) in self.merges.iter().enumerate() {
+            if idx > 0 {
+                out.push(',');
+            }
+            out.push_str(&format!(
+                "{{\"left\":{},\"right\":{},\"a\":{},\"b\":{},\"distance\":{},\"size\":{}}}",
+                merge.left,
+                merge.right,
+                merge.a,
+                merge.b,
+                self.height(merge),
+                merge.size
+            ));
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+/// Index the points, rejecting inputs with fewer than two of them.
+fn build_tree(points: &Points, metric: Metric) -> Result<KdTree, String> {
     let n = points.len();
     if n == 0 {
         return Err("input contained no points".into());
     }
-    if n > u16::MAX as usize {
+    if n > u32::MAX as usize {
         return Err("too many points".into());
     }
     if n < 2 {
         return Err("need at least two points".into());
     }
+    Ok(KdTree::new(points, metric))
+}
 
-    let total_edges = n * (n - 1) / 2;
-    let limit = pair_limit.min(total_edges);
-
-    let mut smallest: BinaryHeap<Edge> = BinaryHeap::with_capacity(limit.saturating_add(1));
-
-    // Prim's algorithm over the implicit complete graph:
-    // - compute the MST's maximum edge weight (last edge Kruskal would add with unique weights)
-    // - while computing distances, also maintain the `limit` smallest edges for part 1
-    let mut min_dist = vec![u64::MAX; n];
-    let mut parent = vec![0usize; n];
-    min_dist[0] = 0;
-
-    let mut max_edge: Option<(u64, usize, usize)> = None;
-    let mut heap_full = limit == 0;
-    let mut heap_max = Edge { dist: u64::MAX, a: 0, b: 0 };
-
-    let mut remaining: Vec<usize> = (0..n).collect();
-    for _ in 0..n {
-        let mut best = u64::MAX;
-        let mut best_pos = 0usize;
-        let mut u = unsafe { *remaining.get_unchecked(0) };
-        for (pos, &idx) in remaining.iter().enumerate() {
-            let d = unsafe { *min_dist.get_unchecked(idx) };
-            if d < best || (d == best && idx < u) {
-                best = d;
-                best_pos = pos;
-                u = idx;
-            }
+/// Circuits after joining the `pair_limit` closest pairs.
+fn connect(tree: &KdTree, pair_limit: usize) -> DisjointSet {
+    let mut set = DisjointSet::new(tree.len());
+    for edge in EdgeStream::new(tree, pair_limit).take(pair_limit) {
+        set.union(edge.a as usize, edge.b as usize);
+    }
+    set
+}
+
+/// The connection that leaves a single circuit.
+fn last_connection(tree: &KdTree) -> Result<Edge, String> {
+    spanning_tree(tree)
+        .into_iter()
+        .max()
+        .ok_or_else(|| "graph never became fully connected".to_string())
+}
+
+/// Leaves hold at most this many points and are scanned linearly.
+const LEAF_SIZE: usize = 8;
+
+/// Implicit k-d tree: points are permuted so every range `lo..hi` larger than
+/// a leaf has its splitting point at the midpoint, smaller coordinates on the
+/// left, and splits cycle through the axes by depth.
+struct KdTree {
+    dim: usize,
+    metric: Metric,
+    /// Coordinates of the point in each slot, `dim` per slot.
+    coords: Vec<i64>,
+    /// Input index of the point in each slot.
+    ids: Vec<u32>,
+}
+
+impl KdTree {
+    fn new(input: &Points, metric: Metric) -> Self {
+        let mut ids: Vec<u32> = (0..input.len() as u32).collect();
+        build(&mut ids, input, 0);
+        let coords = ids
+            .iter()
+            .flat_map(|&id| input.get(id as usize).iter().copied())
+            .collect();
+        KdTree {
+            dim: input.dim,
+            metric,
+            coords,
+            ids,
         }
-        remaining.swap_remove(best_pos);
+    }
 
-        if u != 0 {
-            let a = unsafe { *parent.get_unchecked(u) };
-            let b = u;
-            match max_edge {
-                Some((d, _, _)) if d >= best => {}
-                _ => max_edge = Some((best, a, b)),
-            }
+    fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    #[inline(always)]
+    fn point(&self, slot: usize) -> &[i64] {
+        unsafe {
+            self.coords
+                .get_unchecked(slot * self.dim..(slot + 1) * self.dim)
         }
+    }
 
-        let pu = unsafe { points.get_unchecked(u) };
-        for &v in remaining.iter() {
-            let pv = unsafe { points.get_unchecked(v) };
-            let dist = sq_dist(pu, pv);
+    /// Call `f(id, dist)` for every point within distance `r` of `q`.
+    fn within(&self, q: &[i64], r: u64, f: &mut impl FnMut(u32, u64)) {
+        self.within_in(0, self.len(), 0, q, r, f);
+    }
 
-            if limit != 0 {
-                if !heap_full {
-                    let (a, b) = if u < v { (u, v) } else { (v, u) };
-                    smallest.push(Edge {
-                        dist,
-                        a: a as u16,
-                        b: b as u16,
-                    });
-                    if smallest.len() == limit {
-                        heap_full = true;
-                        heap_max = unsafe { *smallest.peek().unwrap_unchecked() };
-                    }
-                } else if dist < heap_max.dist {
-                    let (a, b) = if u < v { (u, v) } else { (v, u) };
-                    smallest.pop();
-                    smallest.push(Edge {
-                        dist,
-                        a: a as u16,
-                        b: b as u16,
-                    });
-                    heap_max = unsafe { *smallest.peek().unwrap_unchecked() };
-                } else if dist == heap_max.dist {
-                    let (a, b) = if u < v { (u, v) } else { (v, u) };
-                    let edge = Edge {
-                        dist,
-                        a: a as u16,
-                        b: b as u16,
-                    };
-                    if edge < heap_max {
-                        smallest.pop();
-                        smallest.push(edge);
-                        heap_max = unsafe { *smallest.peek().unwrap_unchecked() };
-                    }
+    fn within_in(
+        &self,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        q: &[i64],
+        r: u64,
+        f: &mut impl FnMut(u32, u64),
+    ) {
+        if hi - lo <= LEAF_SIZE {
+            for slot in lo..hi {
+                let d = self.metric.distance(q, self.point(slot));
+                if d <= r {
+                    f(unsafe { *self.ids.get_unchecked(slot) }, d);
                 }
             }
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let pivot = self.point(mid);
+        let d = self.metric.distance(q, pivot);
+        if d <= r {
+            f(unsafe { *self.ids.get_unchecked(mid) }, d);
+        }
+        let axis = depth % self.dim;
+        let diff = q[axis] - pivot[axis];
+        let (near, far) = if diff < 0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.within_in(near.0, near.1, depth + 1, q, r, f);
+        if self.metric.axis(diff) <= r {
+            self.within_in(far.0, far.1, depth + 1, q, r, f);
+        }
+    }
 
-            let cur = unsafe { *min_dist.get_unchecked(v) };
-            if dist < cur || (dist == cur && u < unsafe { *parent.get_unchecked(v) }) {
-                unsafe {
-                    *min_dist.get_unchecked_mut(v) = dist;
-                    *parent.get_unchecked_mut(v) = u;
+    /// Component shared by every point of the subtree `lo..hi`, or `MIXED`,
+    /// stored at the subtree's midpoint (leaves: first) slot.
+    fn fill_uniform(&self, lo: usize, hi: usize, comp: &[u32], uniform: &mut [u32]) -> u32 {
+        const EMPTY: u32 = u32::MAX - 1;
+        if lo >= hi {
+            return EMPTY;
+        }
+        let (key, value) = if hi - lo <= LEAF_SIZE {
+            let first = comp[lo];
+            let same = comp[lo..hi].iter().all(|&c| c == first);
+            (lo, if same { first } else { MIXED })
+        } else {
+            let mid = lo + (hi - lo) / 2;
+            let left = self.fill_uniform(lo, mid, comp, uniform);
+            let right = self.fill_uniform(mid + 1, hi, comp, uniform);
+            let mut value = comp[mid];
+            for side in [left, right] {
+                if side != EMPTY && side != value {
+                    value = MIXED;
                 }
             }
+            (mid, value)
+        };
+        uniform[key] = value;
+        value
+    }
+}
+
+impl KdTree {
+    /// Bounding box of every subtree as `dim` minima then `dim` maxima, stored
+    /// at `2 * dim` times the subtree's midpoint (leaves: first) slot.
+    fn boxes(&self) -> Vec<i64> {
+        let mut boxes = vec![0; 2 * self.dim * self.len()];
+        self.fill_boxes(0, self.len(), &mut boxes);
+        boxes
+    }
+
+    fn fill_boxes(&self, lo: usize, hi: usize, boxes: &mut [i64]) -> Option<usize> {
+        if lo >= hi {
+            return None;
+        }
+        let dim = self.dim;
+        let (key, children) = if hi - lo <= LEAF_SIZE {
+            (lo, [None, None])
+        } else {
+            let mid = lo + (hi - lo) / 2;
+            let left = self.fill_boxes(lo, mid, boxes);
+            let right = self.fill_boxes(mid + 1, hi, boxes);
+            (mid, [left, right])
+        };
+        let base = 2 * dim * key;
+        let slots = if children == [None, None] {
+            lo..hi
+        } else {
+            key..key + 1
+        };
+        let (mut min, mut max) = (vec![i64::MAX; dim], vec![i64::MIN; dim]);
+        for slot in slots {
+            for (axis, &v) in self.point(slot).iter().enumerate() {
+                min[axis] = min[axis].min(v);
+                max[axis] = max[axis].max(v);
+            }
         }
+        for child in children.into_iter().flatten() {
+            let at = 2 * dim * child;
+            for axis in 0..dim {
+                min[axis] = min[axis].min(boxes[at + axis]);
+                max[axis] = max[axis].max(boxes[at + dim + axis]);
+            }
+        }
+        boxes[base..base + dim].copy_from_slice(&min);
+        boxes[base + dim..base + 2 * dim].copy_from_slice(&max);
+        Some(key)
     }
+}
 
-    let (_max_dist, max_a, max_b) =
-        max_edge.ok_or_else(|| "graph never became fully connected".to_string())?;
+/// Count the points whose edge to one query point sorts at or before `last`.
+/// Cells entirely closer than `last` are counted whole and cells entirely
+/// farther skipped, so a far outlier does not turn this into a pair scan.
+struct PairCount<'a> {
+    tree: &'a KdTree,
+    boxes: &'a [i64],
+    /// Slot of the query point; it is never counted itself.
+    slot: usize,
+    last: Edge,
+    count: u64,
+}
 
-    let mut parents: Vec<u16> = (0..n as u16).collect();
-    let mut sizes = vec![1u32; n];
-    for edge in smallest.into_iter() {
-        let _ = union(edge.a, edge.b, &mut parents, &mut sizes);
+impl PairCount<'_> {
+    fn visit(&mut self, lo: usize, hi: usize) {
+        if lo >= hi {
+            return;
+        }
+        let tree = self.tree;
+        let leaf = hi - lo <= LEAF_SIZE;
+        let key = if leaf { lo } else { lo + (hi - lo) / 2 };
+        let dim = tree.dim;
+        let cell = &self.boxes[2 * dim * key..2 * dim * (key + 1)];
+        let q = tree.point(self.slot);
+        let (mut near, mut far) = (0u64, 0u64);
+        for axis in 0..dim {
+            let (min, max) = (cell[axis], cell[dim + axis]);
+            let gap = (min - q[axis]).max(q[axis] - max).max(0);
+            let span = (q[axis] - min).abs().max((max - q[axis]).abs());
+            near = tree.metric.accumulate(near, gap);
+            far = tree.metric.accumulate(far, span);
+        }
+        if near > self.last.dist {
+            return;
+        }
+        if far < self.last.dist {
+            self.count += (hi - lo) as u64 - (lo..hi).contains(&self.slot) as u64;
+            return;
+        }
+        if leaf {
+            (lo..hi).for_each(|slot| self.consider(slot));
+            return;
+        }
+        self.consider(key);
+        self.visit(lo, key);
+        self.visit(key + 1, hi);
     }
-    let top_after_limit = top_three(&parents, &sizes);
 
-    let p1 = top_after_limit[0] as u64 * top_after_limit[1] as u64 * top_after_limit[2] as u64;
-    let ax = unsafe { *points.get_unchecked(max_a) }[0];
-    let bx = unsafe { *points.get_unchecked(max_b) }[0];
-    let p2 = (ax * bx) as u64;
-    Ok((p1, p2))
+    #[inline(always)]
+    fn consider(&mut self, slot: usize) {
+        if slot == self.slot {
+            return;
+        }
+        let tree = self.tree;
+        let d = tree
+            .metric
+            .distance(tree.point(self.slot), tree.point(slot));
+        let edge = Edge::new(d, tree.ids[self.slot], tree.ids[slot]);
+        if edge <= self.last {
+            self.count += 1;
+        }
+    }
 }
 
-#[inline(always)]
-fn sq_dist(a: &[i64; 3], b: &[i64; 3]) -> u64 {
-    let dx = unsafe { *a.get_unchecked(0) } - unsafe { *b.get_unchecked(0) };
-    let dy = unsafe { *a.get_unchecked(1) } - unsafe { *b.get_unchecked(1) };
-    let dz = unsafe { *a.get_unchecked(2) } - unsafe { *b.get_unchecked(2) };
-    (dx * dx + dy * dy + dz * dz) as u64
+/// Search for the cheapest edge from one point to any other component.
+struct ForeignSearch<'a> {
+    tree: &'a KdTree,
+    comp: &'a [u32],
+    uniform: &'a [u32],
+    /// Slot of the query point.
+    slot: usize,
+    own: u32,
+    best: Edge,
+    /// Per-axis gap from the query to the current cell.
+    off: Vec<i64>,
 }
 
-#[inline(always)]
-fn top_three(parents: &[u16], sizes: &[u32]) -> [u32; 3] {
-    let mut top = [0u32; 3];
-    for idx in 0..parents.len() {
-        if unsafe { *parents.get_unchecked(idx) } == idx as u16 {
-            let sz = unsafe { *sizes.get_unchecked(idx) };
-            if sz > top[0] {
-                top[2] = top[1];
-                top[1] = top[0];
-                top[0] = sz;
-            } else if sz > top[1] {
-                top[2] = top[1];
-                top[1] = sz;
-            } else if sz > top[2] {
-                top[2] = sz;
+impl ForeignSearch<'_> {
+    /// `bound` is a lower bound on the distance from the query to anything in
+    /// the subtree, derived from `off`.
+    fn visit(&mut self, lo: usize, hi: usize, depth: usize, bound: u64) {
+        if lo >= hi || bound > self.best.dist {
+            return;
+        }
+        if hi - lo <= LEAF_SIZE {
+            if self.uniform[lo] != self.own {
+                (lo..hi).for_each(|slot| self.consider(slot));
+            }
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if self.uniform[mid] == self.own {
+            return;
+        }
+        self.consider(mid);
+        let tree = self.tree;
+        let axis = depth % tree.dim;
+        let diff = tree.point(self.slot)[axis] - tree.point(mid)[axis];
+        let (near, far) = if diff < 0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.visit(near.0, near.1, depth + 1, bound);
+        let old = std::mem::replace(&mut self.off[axis], diff);
+        let far_bound = tree.metric.rebound(bound, old, &self.off, axis);
+        self.visit(far.0, far.1, depth + 1, far_bound);
+        self.off[axis] = old;
+    }
+
+    #[inline(always)]
+    fn consider(&mut self, slot: usize) {
+        if unsafe { *self.comp.get_unchecked(slot) } == self.own {
+            return;
+        }
+        let tree = self.tree;
+        let d = tree
+            .metric
+            .distance(tree.point(self.slot), tree.point(slot));
+        if d <= self.best.dist {
+            let id = unsafe { *tree.ids.get_unchecked(self.slot) };
+            let edge = Edge::new(d, id, unsafe { *tree.ids.get_unchecked(slot) });
+            if edge < self.best {
+                self.best = edge;
+            }
+        }
+    }
+}
+
+const MIXED: u32 = u32::MAX;
+
+fn build(ids: &mut [u32], points: &Points, depth: usize) {
+    if ids.len() <= LEAF_SIZE {
+        return;
+    }
+    let axis = depth % points.dim;
+    let mid = ids.len() / 2;
+    ids.select_nth_unstable_by_key(mid, |&id| points.get(id as usize)[axis]);
+    let (left, right) = ids.split_at_mut(mid);
+    build(left, points, depth + 1);
+    build(&mut right[1..], points, depth + 1);
+}
+
+/// All pairs in increasing edge order, produced lazily: each batch collects
+/// the pairs whose distance falls in the next band `(covered, radius]` with
+/// range queries, then the band's radius doubles.
+struct EdgeStream<'a> {
+    tree: &'a KdTree,
+    /// Current band, largest edge first.
+    pending: Vec<Edge>,
+    /// Every pair with distance up to this has been produced.
+    covered: Option<u64>,
+    radius: u64,
+}
+
+impl<'a> EdgeStream<'a> {
+    /// `expected` is how many edges the caller will likely take; it only sizes
+    /// the first band.
+    fn new(tree: &'a KdTree, expected: usize) -> Self {
+        let n = tree.len() as f64;
+        // Extent of the middle 98% along each axis, so a few far outliers do
+        // not blow the first band up to every pair.
+        let mut volume = 1.0f64;
+        let mut values: Vec<i64> = Vec::with_capacity(tree.len());
+        for axis in 0..tree.dim {
+            values.clear();
+            values.extend(tree.coords.iter().skip(axis).step_by(tree.dim));
+            let trim = tree.len() / 100;
+            let last = tree.len() - 1 - trim;
+            let hi = *values.select_nth_unstable(last).1;
+            let lo = *values[..last].select_nth_unstable(trim).1;
+            volume *= (hi - lo).max(1) as f64;
+        }
+        // Uniform density, approximating every ball by its bounding cube:
+        // pairs within r ~ n^2/2 * (2r)^dim / volume.
+        let target = (expected as f64).max(n);
+        let dim = tree.dim as f64;
+        let r = (2.0 * volume * target / (n * n)).powf(1.0 / dim) / 2.0;
+        let radius = match tree.metric {
+            Metric::Euclidean => r * r,
+            Metric::Manhattan | Metric::Chebyshev => r,
+        };
+        EdgeStream {
+            tree,
+            pending: Vec::new(),
+            covered: None,
+            radius: radius.ceil().max(1.0) as u64,
+        }
+    }
+
+    fn fill_band(&mut self) {
+        let tree = self.tree;
+        let (covered, radius) = (self.covered, self.radius);
+        for slot in 0..tree.len() {
+            let id = tree.ids[slot];
+            tree.within(tree.point(slot), radius, &mut |other, d| {
+                if other > id && covered.is_none_or(|c| d > c) {
+                    self.pending.push(Edge {
+                        dist: d,
+                        a: id,
+                        b: other,
+                    });
+                }
+            });
+        }
+        self.pending.sort_unstable_by(|x, y| y.cmp(x));
+        self.covered = Some(radius);
+        self.radius = radius.saturating_mul(2);
+    }
+}
+
+impl Iterator for EdgeStream<'_> {
+    type Item = Edge;
+
+    fn next(&mut self) -> Option<Edge> {
+        while self.pending.is_empty() {
+            if self.covered == Some(u64::MAX) {
+                return None;
             }
+            self.fill_band();
         }
+        self.pending.pop()
     }
-    top
 }
 
-fn parse_points(input: &str) -> Result<Vec<[i64; 3]>, String> {
+/// Euclidean minimum spanning tree by Borůvka's algorithm: every round, each
+/// component takes its cheapest edge to another component, found with k-d tree
+/// searches that skip subtrees lying entirely inside the component.
+fn spanning_tree(tree: &KdTree) -> Vec<Edge> {
+    let n = tree.len();
+    let mut set: DisjointSet = DisjointSet::new(n);
+    let mut comp = vec![0u32; n];
+    let mut uniform = vec![MIXED; n];
+    let mut cheapest = vec![Edge::NONE; n];
+    // Distance from each slot to the nearest point outside its component so
+    // far; merging components can only increase it.
+    let mut lower = vec![0u64; n];
+    let mut edges = Vec::with_capacity(n - 1);
+
+    while edges.len() + 1 < n {
+        for (slot, c) in comp.iter_mut().enumerate() {
+            *c = set.find(tree.ids[slot] as usize) as u32;
+        }
+        tree.fill_uniform(0, n, &comp, &mut uniform);
+        for slot in 0..n {
+            let own = comp[slot];
+            // The component's best so far bounds the search.
+            let best = cheapest[own as usize];
+            if lower[slot] > best.dist {
+                continue;
+            }
+            let mut search = ForeignSearch {
+                tree,
+                comp: &comp,
+                uniform: &uniform,
+                slot,
+                own,
+                best,
+                off: vec![0; tree.dim],
+            };
+            search.visit(0, n, 0, 0);
+            lower[slot] = search.best.dist;
+            cheapest[own as usize] = search.best;
+        }
+        let before = edges.len();
+        for edge in cheapest.iter_mut() {
+            if *edge != Edge::NONE && set.union(edge.a as usize, edge.b as usize) {
+                edges.push(*edge);
+            }
+            *edge = Edge::NONE;
+        }
+        if edges.len() == before {
+            break;
+        }
+    }
+    edges
+}
+
+/// Points with the same number of coordinates each, stored flat.
+struct Points {
+    dim: usize,
+    coords: Vec<i64>,
+}
+
+impl Points {
+    fn len(&self) -> usize {
+        self.coords.len() / self.dim
+    }
+
+    #[inline(always)]
+    fn get(&self, idx: usize) -> &[i64] {
+        &self.coords[idx * self.dim..(idx + 1) * self.dim]
+    }
+}
+
+fn parse_points(input: &str) -> Result<Points, String> {
     let bytes = input.as_bytes();
     let len = bytes.len();
     let mut idx = 0usize;
-    let mut points: Vec<[i64; 3]> = Vec::with_capacity(1024);
+    let mut coords: Vec<i64> = Vec::with_capacity(3 * 1024);
+    let mut dim = 0usize;
+    let mut count = 0usize;
 
     while idx < len {
         while idx < len {
@@ -209,11 +906,22 @@ fn parse_points(input: &str) -> Result<Vec<[i64; 3]>, String> {
             break;
         }
 
-        let x = parse_int(bytes, len, &mut idx)?;
-        expect_char(bytes, len, &mut idx, b',')?;
-        let y = parse_int(bytes, len, &mut idx)?;
-        expect_char(bytes, len, &mut idx, b',')?;
-        let z = parse_int(bytes, len, &mut idx)?;
+        let start = coords.len();
+        coords.push(parse_int(bytes, len, &mut idx)? as i64);
+        while idx < len && unsafe { *bytes.get_unchecked(idx) } == b',' {
+            expect_char(bytes, len, &mut idx, b',')?;
+            coords.push(parse_int(bytes, len, &mut idx)? as i64);
+        }
+        let found = coords.len() - start;
+        count += 1;
+        if dim == 0 {
+            dim = found;
+        } else if found != dim {
+            return Err(format!(
+                "point {} has {} coordinates, expected {}",
+                count, found, dim
+            ));
+        }
 
         while idx < len {
             let b = unsafe { *bytes.get_unchecked(idx) };
@@ -228,11 +936,12 @@ fn parse_points(input: &str) -> Result<Vec<[i64; 3]>, String> {
                 break;
             }
         }
-
-        points.push([x as i64, y as i64, z as i64]);
     }
 
-    Ok(points)
+    Ok(Points {
+        dim: dim.max(1),
+        coords,
+    })
 }
 
 #[inline(always)]
@@ -276,45 +985,11 @@ fn expect_char(bytes: &[u8], len: usize, idx: &mut usize, expected: u8) -> Resul
     Ok(())
 }
 
-#[inline(always)]
-fn find(x: u16, parents: &mut [u16]) -> u16 {
-    let mut p = x;
-    while unsafe { *parents.get_unchecked(p as usize) } != p {
-        p = unsafe { *parents.get_unchecked(p as usize) };
-    }
-    let mut cur = x;
-    while cur != p {
-        let next = unsafe { *parents.get_unchecked(cur as usize) };
-        unsafe {
-            *parents.get_unchecked_mut(cur as usize) = p;
-        }
-        cur = next;
-    }
-    p
-}
-
-#[inline(always)]
-fn union(a: u16, b: u16, parents: &mut [u16], sizes: &mut [u32]) -> bool {
-    let mut ra = find(a, parents);
-    let mut rb = find(b, parents);
-    if ra == rb {
-        return false;
-    }
-    let sa = unsafe { *sizes.get_unchecked(ra as usize) };
-    let sb = unsafe { *sizes.get_unchecked(rb as usize) };
-    if sa < sb {
-        std::mem::swap(&mut ra, &mut rb);
-    }
-    unsafe {
-        *parents.get_unchecked_mut(rb as usize) = ra;
-        *sizes.get_unchecked_mut(ra as usize) = sa + sb;
-    }
-    true
-}
-
 #[cfg(test)]
 mod tests {
-    use super::{both, part2, solve_with_limit};
+    use super::{both, circuits, dendrogram, part2, solve_with, solve_with_limit, Metric};
+    use crate::disjoint_set::DisjointSet;
+    use crate::test_rng::XorShift;
 
     const EXAMPLE: &str = "162,817,812\n\
 57,618,57\n\
@@ -347,8 +1022,136 @@ mod tests {
         assert_eq!(part2(EXAMPLE).unwrap(), 25_272);
     }
 
+    #[test]
+    fn example_circuit_report() {
+        let report = circuits(EXAMPLE, 10, Metric::Euclidean).unwrap();
+        assert_eq!(report.pairs, 10);
+        assert_eq!(&report.sizes()[..4], &[5, 4, 2, 2]);
+        assert_eq!(report.sizes().iter().sum::<usize>(), 20);
+        assert_eq!(report.circuits.len(), 11);
+        // Joining 216,146,977 and 117,168,530 completes the network.
+        assert_eq!(report.final_pair, (10, 12));
+        assert_eq!(
+            circuits(EXAMPLE, report.connected_at as usize, Metric::Euclidean)
+                .unwrap()
+                .circuits
+                .len(),
+            1
+        );
+        assert_eq!(
+            circuits(EXAMPLE, report.connected_at as usize - 1, Metric::Euclidean)
+                .unwrap()
+                .circuits
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn dendrogram_cuts_and_exports() {
+        let tree = dendrogram(EXAMPLE, Metric::Euclidean).unwrap();
+        assert_eq!(tree.merges().len(), 19);
+        let last = tree.merges()[18];
+        assert_eq!(
+            (last.a, last.b, last.size, last.left.max(last.right)),
+            (10, 12, 20, 37)
+        );
+        assert_eq!(tree.cut_by_count(1).len(), 1);
+        assert_eq!(tree.cut_by_count(20).len(), 20);
+        let report = circuits(EXAMPLE, 10, Metric::Euclidean).unwrap();
+        assert_eq!(tree.cut_by_count(report.circuits.len()), report.circuits);
+        assert_eq!(tree.cut_by_distance(last.dist - 1).len(), 2);
+
+        let small = dendrogram("0,0,0\n3,4,0\n0,0,1\n", Metric::Euclidean).unwrap();
+        assert_eq!(small.to_newick(), "((0:1,2:1):4,1:5);");
+        assert_eq!(
+            small.to_json(),
+            "{\"leaves\":3,\"merges\":[\
+             {\"left\":0,\"right\":2,\"a\":0,\"b\":2,\"distance\":1,\"size\":2},\
+             {\"left\":3,\"right\":1,\"a\":0,\"b\":1,\"distance\":5,\"size\":3}]}"
+        );
+    }
+
     #[test]
     fn puzzle_input_runs() {
         let _ = both(super::INPUT).unwrap();
     }
+
+    #[test]
+    fn matches_brute_force_kruskal() {
+        let mut rng = XorShift::new(0x2545_f491_4f6c_dd1d);
+        let mut next = |bound: u64| rng.below(bound) as i64;
+        // The last flag adds a far outlier, which joins last.
+        let cases = [
+            (2, 3, 5, 1, Metric::Euclidean, false),
+            (40, 3, 6, 30, Metric::Euclidean, false),
+            (300, 3, 50, 200, Metric::Euclidean, false),
+            (300, 3, 50, 200, Metric::Euclidean, true),
+            (500, 3, 100_000, 1000, Metric::Euclidean, false),
+            (200, 2, 30, 150, Metric::Manhattan, false),
+            (250, 4, 20, 300, Metric::Chebyshev, true),
+            (150, 5, 1000, 100, Metric::Manhattan, false),
+        ];
+        for (n, dim, span, limit, metric, outlier) in cases {
+            // Small spans force many equal distances, exercising tie-breaks.
+            let mut points: Vec<Vec<i64>> = (0..n)
+                .map(|_| (0..dim).map(|_| next(span) + 1).collect())
+                .collect();
+            if outlier {
+                points[n / 2] = vec![span as i64 * 40; dim];
+            }
+            let input: String = points
+                .iter()
+                .map(|p| {
+                    let coords: Vec<String> = p.iter().map(|c| c.to_string()).collect();
+                    coords.join(",") + "\n"
+                })
+                .collect();
+
+            let mut edges = Vec::new();
+            for a in 0..n {
+                for b in a + 1..n {
+                    let diffs = points[a].iter().zip(&points[b]).map(|(x, y)| (x - y).abs());
+                    let d = match metric {
+                        Metric::Euclidean => diffs.map(|d| d * d).sum(),
+                        Metric::Manhattan => diffs.sum(),
+                        Metric::Chebyshev => diffs.max().unwrap(),
+                    };
+                    edges.push((d, a, b));
+                }
+            }
+            edges.sort_unstable();
+            let mut set: DisjointSet = DisjointSet::new(n);
+            for &(_, a, b) in edges.iter().take(limit) {
+                set.union(a, b);
+            }
+            let mut top = set.top_sizes(3);
+            top.resize(3, 0);
+            let p1 = top.iter().map(|&s| s as u64).product::<u64>();
+            let mut set: DisjointSet = DisjointSet::new(n);
+            let mut last = (0, 0);
+            let mut connected_at = 0;
+            for (idx, &(_, a, b)) in edges.iter().enumerate() {
+                if set.union(a, b) {
+                    last = (a, b);
+                    connected_at = idx as u64 + 1;
+                }
+            }
+            assert_eq!(set.components(), 1);
+            let p2 = (points[last.0][0] * points[last.1][0]) as u64;
+            assert_eq!(
+                solve_with(&input, limit, metric).unwrap(),
+                (p1, p2),
+                "n = {}, dim = {}, {:?}",
+                n,
+                dim,
+                metric
+            );
+            let report = circuits(&input, limit, metric).unwrap();
+            assert_eq!(report.answers, (p1, p2));
+            assert_eq!(report.final_pair, last);
+            assert_eq!(report.connected_at, connected_at, "n = {}, {:?}", n, metric);
+        }
+        assert!(solve_with("1,2,3\n4,5\n", 1, Metric::Euclidean).is_err());
+    }
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeSet;
+
 pub static INPUT: &str = include_str!("../inputs/09.txt");
 
 /// Part 1: largest axis-aligned rectangle that uses red tiles for two opposite corners.
@@ -6,14 +8,13 @@ pub fn part1(input: &str) -> Result<u64, String> {
     if points.len() < 2 {
         return Err("need at least two red tiles".into());
     }
-    Ok(max_area_any(&points))
+    Ok(largest_pair(&points).area())
 }
 
 /// Part 2: largest rectangle whose tiles are all red or green (inside the loop).
 pub fn part2(input: &str) -> Result<u64, String> {
-    let points = parse_points(input)?;
-    let coverage = build_coverage(&points)?;
-    Ok(max_area_within_green(&points, &coverage))
+    let polygon = RectilinearPolygon::parse(input)?;
+    Ok(polygon.largest_red_cornered().area())
 }
 
 /// Compute both parts with a shared parse.
@@ -22,9 +23,9 @@ pub fn both(input: &str) -> Result<(u64, u64), String> {
     if points.len() < 2 {
         return Err("need at least two red tiles".into());
     }
-    let part1 = max_area_any(&points);
-    let coverage = build_coverage(&points)?;
-    let part2 = max_area_within_green(&points, &coverage);
+    let part1 = largest_pair(&points).area();
+    let polygon = RectilinearPolygon::new(points)?;
+    let part2 = polygon.largest_red_cornered().area();
     Ok((part1, part2))
 }
 
@@ -75,14 +76,11 @@ fn parse_points(input: &str) -> Result<Vec<[i32; 2]>, String> {
 }
 
 /// Part 1: Find largest rectangle with red tiles at opposite corners.
-/// Simple O(n²) max scan.
+/// Simple O(n²) max scan over at least two points.
 #[inline(always)]
-fn max_area_any(points: &[[i32; 2]]) -> u64 {
+fn largest_pair(points: &[[i32; 2]]) -> Rectangle {
     let n = points.len();
-    if n < 2 {
-        return 0;
-    }
-
+    let mut best = (0, 1);
     let mut max_area: u64 = 0;
     for i in 0..n {
         let pi = unsafe { *points.get_unchecked(i) };
@@ -93,10 +91,368 @@ fn max_area_any(points: &[[i32; 2]]) -> u64 {
             let area = dx * dy;
             if area > max_area {
                 max_area = area;
+                best = (i, j);
+            }
+        }
+    }
+    Rectangle::spanning(points[best.0], points[best.1])
+}
+
+/// The loop of red tiles: each vertex is joined to the next (and the last to
+/// the first) by a straight run of green tiles, and everything the loop encloses
+/// is green as well. Queries count whole tiles, boundary included.
+pub struct RectilinearPolygon {
+    vertices: Vec<[i32; 2]>,
+    coverage: Coverage,
+}
+
+impl RectilinearPolygon {
+    /// Validate the loop and build its coverage grid. Errors name the offending
+    /// vertices: duplicates, edges that are not horizontal or vertical, and edges
+    /// that touch or cross anywhere other than a shared vertex.
+    pub fn new(vertices: Vec<[i32; 2]>) -> Result<Self, String> {
+        validate(&vertices)?;
+        let coverage = build_coverage(&vertices)?;
+        Ok(Self { vertices, coverage })
+    }
+
+    /// Parse one `x,y` red tile per line, in loop order.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        Self::new(parse_points(input)?)
+    }
+
+    pub fn vertices(&self) -> &[[i32; 2]] {
+        &self.vertices
+    }
+
+    /// Tiles inside or on the loop.
+    pub fn area(&self) -> u64 {
+        self.coverage.prefix[self.coverage.prefix.len() - 1]
+    }
+
+    /// Tiles on the loop itself: the total edge length.
+    pub fn perimeter(&self) -> u64 {
+        let n = self.vertices.len();
+        (0..n)
+            .map(|idx| {
+                let a = self.vertices[idx];
+                let b = self.vertices[(idx + 1) % n];
+                (a[0] - b[0]).unsigned_abs() as u64 + (a[1] - b[1]).unsigned_abs() as u64
+            })
+            .sum()
+    }
+
+    /// Largest rectangle with red tiles at two opposite corners, whatever
+    /// lies inside (part 1).
+    pub fn largest_red_pair(&self) -> Rectangle {
+        largest_pair(&self.vertices)
+    }
+
+    /// The red and green tiles as disjoint rectangles, one per covered span of
+    /// each compressed row, top to bottom.
+    pub fn green_cells(&self) -> impl Iterator<Item = Rectangle> + '_ {
+        let cov = &self.coverage;
+        cov.rows.iter().enumerate().flat_map(move |(row, spans)| {
+            spans.iter().map(move |&(first, last)| Rectangle {
+                min: [first, cov.ys[row]],
+                max: [last, cov.ys[row + 1] - 1],
+            })
+        })
+    }
+
+    /// Whether `tile` is red or green.
+    pub fn contains(&self, tile: [i32; 2]) -> bool {
+        self.contains_rect(tile, tile)
+    }
+
+    /// Whether every tile of the rectangle with opposite corners `a` and `b` is
+    /// red or green.
+    pub fn contains_rect(&self, a: [i32; 2], b: [i32; 2]) -> bool {
+        let cov = &self.coverage;
+        let Some((x_lo, x_hi)) = cell_span(&cov.xs, a[0].min(b[0]), a[0].max(b[0])) else {
+            return false;
+        };
+        let Some((y_lo, y_hi)) = cell_span(&cov.ys, a[1].min(b[1]), a[1].max(b[1])) else {
+            return false;
+        };
+        // Cells are uniformly covered or not, so the rectangle is covered exactly
+        // when the cells it overlaps are.
+        let width = (cov.xs[x_hi] as i64 - cov.xs[x_lo] as i64) as u64;
+        let height = (cov.ys[y_hi] as i64 - cov.ys[y_lo] as i64) as u64;
+        let p = |yi: usize, xi: usize| cov.prefix[yi * cov.stride + xi];
+        let green = p(y_hi, x_hi) + p(y_lo, x_lo) - p(y_lo, x_hi) - p(y_hi, x_lo);
+        green == width * height
+    }
+
+    /// Largest rectangle with red tiles at two opposite corners and only red or
+    /// green tiles inside (part 2).
+    ///
+    /// Every pair of red tiles is checked against the coverage prefix sums in
+    /// `O(1)`, `O(n^2)` for `n` red tiles. Building the compressed grid is
+    /// already `O(n^2)`, so a sub-quadratic pair search would not lower the cost
+    /// of part 2 as a whole.
+    pub fn largest_red_cornered(&self) -> Rectangle {
+        let cov = &self.coverage;
+        // Each red tile owns a compressed cell of its own in both directions.
+        let cells: Vec<[usize; 2]> = self
+            .vertices
+            .iter()
+            .map(|&[x, y]| [lower_bound(&cov.xs, x), lower_bound(&cov.ys, y)])
+            .collect();
+        let p = |yi: usize, xi: usize| cov.prefix[yi * cov.stride + xi];
+
+        let mut best = Rectangle::spanning(self.vertices[0], self.vertices[0]);
+        let mut best_area = best.area();
+        for (i, (&a, &ca)) in self.vertices.iter().zip(&cells).enumerate() {
+            for (&b, &cb) in self.vertices[i + 1..].iter().zip(&cells[i + 1..]) {
+                let rect = Rectangle::spanning(a, b);
+                let area = rect.area();
+                if area <= best_area {
+                    continue;
+                }
+                let (x_lo, x_hi) = (ca[0].min(cb[0]), ca[0].max(cb[0]) + 1);
+                let (y_lo, y_hi) = (ca[1].min(cb[1]), ca[1].max(cb[1]) + 1);
+                let green = p(y_hi, x_hi) + p(y_lo, x_lo) - p(y_lo, x_hi) - p(y_hi, x_lo);
+                if green == area {
+                    best = rect;
+                    best_area = area;
+                }
+            }
+        }
+        best
+    }
+
+    /// Largest rectangle of red or green tiles with corners anywhere.
+    ///
+    /// A largest-rectangle-in-histogram sweep over the compressed grid: each
+    /// column cell's bar is the covered height ending at the current row, and a
+    /// monotonic stack yields every maximal rectangle resting on that row, in
+    /// `O(cells)` overall.
+    pub fn largest_rectangle(&self) -> Rectangle {
+        let cov = &self.coverage;
+        let width = cov.xs.len() - 1;
+        let mut depth = vec![0i64; width];
+        let mut covered = vec![false; width];
+        // (first cell, depth) with strictly increasing depths.
+        let mut stack: Vec<(usize, i64)> = Vec::with_capacity(width);
+        let mut best = Rectangle::spanning(self.vertices[0], self.vertices[0]);
+        let mut best_area = best.area();
+        for (row, spans) in cov.rows.iter().enumerate() {
+            covered.fill(false);
+            for &(l, r) in spans {
+                covered[lower_bound(&cov.xs, l)..lower_bound(&cov.xs, r + 1)].fill(true);
+            }
+            let bottom = cov.ys[row + 1] as i64;
+            let dy = bottom - cov.ys[row] as i64;
+            for (d, &c) in depth.iter_mut().zip(&covered) {
+                *d = if c { *d + dy } else { 0 };
+            }
+
+            for cell in 0..=width {
+                let h = depth.get(cell).copied().unwrap_or(0);
+                let mut start = cell;
+                while let Some(&(first, top)) = stack.last() {
+                    if top < h {
+                        break;
+                    }
+                    stack.pop();
+                    let w = cov.xs[cell] as i64 - cov.xs[first] as i64;
+                    if (w * top) as u64 > best_area {
+                        best = Rectangle {
+                            min: [cov.xs[first], (bottom - top) as i32],
+                            max: [cov.xs[cell] - 1, bottom as i32 - 1],
+                        };
+                        best_area = best.area();
+                    }
+                    start = first;
+                }
+                if h > 0 {
+                    stack.push((start, h));
+                }
+            }
+        }
+        best
+    }
+}
+
+/// A block of tiles given by its inclusive corners.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rectangle {
+    pub min: [i32; 2],
+    pub max: [i32; 2],
+}
+
+impl Rectangle {
+    /// The rectangle with opposite corners `a` and `b`.
+    pub fn spanning(a: [i32; 2], b: [i32; 2]) -> Self {
+        Self {
+            min: [a[0].min(b[0]), a[1].min(b[1])],
+            max: [a[0].max(b[0]), a[1].max(b[1])],
+        }
+    }
+
+    /// Tiles in the rectangle.
+    pub fn area(&self) -> u64 {
+        (self.max[0] as i64 - self.min[0] as i64 + 1) as u64
+            * (self.max[1] as i64 - self.min[1] as i64 + 1) as u64
+    }
+}
+
+/// Compressed cells `lo..hi` overlapping tiles `first..=last`, if they all lie
+/// within the grid.
+fn cell_span(bounds: &[i32], first: i32, last: i32) -> Option<(usize, usize)> {
+    if first < bounds[0] || last >= bounds[bounds.len() - 1] {
+        return None;
+    }
+    let lo = bounds.partition_point(|&v| v <= first) - 1;
+    let hi = lower_bound(bounds, last + 1);
+    Some((lo, hi))
+}
+
+/// An axis-aligned edge: `edge` runs from vertex `edge` to the next one along
+/// the line `at`, covering `lo..=hi` on the other axis.
+#[derive(Clone, Copy)]
+struct Segment {
+    at: i32,
+    lo: i32,
+    hi: i32,
+    edge: usize,
+}
+
+fn validate(vertices: &[[i32; 2]]) -> Result<(), String> {
+    let n = vertices.len();
+    if n < 3 {
+        return Err("need at least three red tiles to form a loop".into());
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_unstable_by_key(|&idx| (vertices[idx], idx));
+    for pair in order.windows(2) {
+        let [x, y] = vertices[pair[0]];
+        if vertices[pair[1]] == [x, y] {
+            return Err(format!(
+                "vertices {} and {} are both at {},{}",
+                pair[0], pair[1], x, y
+            ));
+        }
+    }
+
+    let mut horizontal = Vec::with_capacity(n / 2 + 1);
+    let mut vertical = Vec::with_capacity(n / 2 + 1);
+    for edge in 0..n {
+        let a = vertices[edge];
+        let b = vertices[(edge + 1) % n];
+        if a[1] == b[1] {
+            let (lo, hi) = (a[0].min(b[0]), a[0].max(b[0]));
+            horizontal.push(Segment {
+                at: a[1],
+                lo,
+                hi,
+                edge,
+            });
+        } else if a[0] == b[0] {
+            let (lo, hi) = (a[1].min(b[1]), a[1].max(b[1]));
+            vertical.push(Segment {
+                at: a[0],
+                lo,
+                hi,
+                edge,
+            });
+        } else {
+            return Err(format!(
+                "edge {} is not axis-aligned: {},{} to {},{}",
+                edge_name(edge, n),
+                a[0],
+                a[1],
+                b[0],
+                b[1]
+            ));
+        }
+    }
+
+    check_collinear(&mut horizontal, n, |at, v| [v, at])?;
+    check_collinear(&mut vertical, n, |at, v| [at, v])?;
+    check_crossings(&horizontal, &vertical, n)
+}
+
+/// Collinear edges may only meet end to end at the vertex they share.
+fn check_collinear(
+    segments: &mut [Segment],
+    n: usize,
+    point: impl Fn(i32, i32) -> [i32; 2],
+) -> Result<(), String> {
+    segments.sort_unstable_by_key(|s| (s.at, s.lo, s.hi));
+    // Furthest-reaching edge so far on the current line.
+    let mut reach: Option<Segment> = None;
+    for &s in segments.iter() {
+        if let Some(r) = reach.filter(|r| r.at == s.at) {
+            if s.lo < r.hi || (s.lo == r.hi && !adjacent(r.edge, s.edge, n)) {
+                return Err(intersection(r.edge, s.edge, n, point(s.at, s.lo)));
             }
         }
+        reach = Some(s);
     }
-    max_area
+    Ok(())
+}
+
+/// Horizontal and vertical edges may only meet at the vertex they share. Sweeps
+/// left to right keeping the horizontal edges that span the current column.
+fn check_crossings(horizontal: &[Segment], vertical: &[Segment], n: usize) -> Result<(), String> {
+    // (x, kind, index): horizontal edges open before and close after the
+    // vertical edges at the same x are checked, so touching ends count.
+    let mut events: Vec<(i32, u8, usize)> =
+        Vec::with_capacity(2 * horizontal.len() + vertical.len());
+    for (idx, h) in horizontal.iter().enumerate() {
+        events.push((h.lo, 0, idx));
+        events.push((h.hi, 2, idx));
+    }
+    for (idx, v) in vertical.iter().enumerate() {
+        events.push((v.at, 1, idx));
+    }
+    events.sort_unstable();
+
+    let mut active: BTreeSet<(i32, usize)> = BTreeSet::new();
+    for (x, kind, idx) in events {
+        match kind {
+            0 => {
+                active.insert((horizontal[idx].at, horizontal[idx].edge));
+            }
+            2 => {
+                active.remove(&(horizontal[idx].at, horizontal[idx].edge));
+            }
+            _ => {
+                let v = vertical[idx];
+                // At most two of the spanned edges are the vertical edge's
+                // neighbours.
+                let hit = active
+                    .range((v.lo, 0)..=(v.hi, usize::MAX))
+                    .find(|&&(_, edge)| !adjacent(edge, v.edge, n));
+                if let Some(&(y, edge)) = hit {
+                    return Err(intersection(edge, v.edge, n, [x, y]));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[inline]
+fn adjacent(e: usize, f: usize, n: usize) -> bool {
+    (e + 1) % n == f || (f + 1) % n == e
+}
+
+fn edge_name(edge: usize, n: usize) -> String {
+    format!("{}-{}", edge, (edge + 1) % n)
+}
+
+fn intersection(e: usize, f: usize, n: usize, at: [i32; 2]) -> String {
+    format!(
+        "loop intersects itself: edges {} and {} meet at {},{}",
+        edge_name(e.min(f), n),
+        edge_name(e.max(f), n),
+        at[0],
+        at[1]
+    )
 }
 
 struct Coverage {
@@ -104,6 +460,8 @@ struct Coverage {
     stride: usize,
     xs: Vec<i32>,
     ys: Vec<i32>,
+    /// Covered tile spans `(first, last)` of each compressed row, sorted.
+    rows: Vec<Vec<(i32, i32)>>,
 }
 
 #[inline(always)]
@@ -227,77 +585,10 @@ fn build_coverage(points: &[[i32; 2]]) -> Result<Coverage, String> {
         stride,
         xs,
         ys,
+        rows: merged_spans,
     })
 }
 
-/// Part 2: Find largest rectangle that is entirely covered by green/red tiles.
-/// Uses precomputed O(1) index lookups instead of binary search.
-#[inline(always)]
-fn max_area_within_green(points: &[[i32; 2]], coverage: &Coverage) -> u64 {
-    let n = points.len();
-    if n < 2 {
-        return 0;
-    }
-
-    // Precompute index lookups for each point's coordinates.
-    let mut point_indices: Vec<(usize, usize, usize, usize)> = Vec::with_capacity(n);
-    for &[x, y] in points {
-        let xi = lower_bound(&coverage.xs, x);
-        let xi1 = lower_bound(&coverage.xs, x + 1);
-        let yi = lower_bound(&coverage.ys, y);
-        let yi1 = lower_bound(&coverage.ys, y + 1);
-        point_indices.push((xi, xi1, yi, yi1));
-    }
-
-    let mut max_area: u64 = 0;
-    let stride = coverage.stride;
-    let prefix = &coverage.prefix;
-
-    for i in 0..n {
-        let pi = unsafe { *points.get_unchecked(i) };
-        let (xi1, xi1_plus, yi1, yi1_plus) = unsafe { *point_indices.get_unchecked(i) };
-
-        for j in (i + 1)..n {
-            let pj = unsafe { *points.get_unchecked(j) };
-            let dx = (pi[0] - pj[0]).unsigned_abs() as u64 + 1;
-            let dy = (pi[1] - pj[1]).unsigned_abs() as u64 + 1;
-            let area = dx * dy;
-
-            // Skip if this pair can't beat current max
-            if area <= max_area {
-                continue;
-            }
-
-            let (xj1, xj1_plus, yj1, yj1_plus) = unsafe { *point_indices.get_unchecked(j) };
-
-            // Determine the rectangle bounds in index space
-            let (xi_lo, xi_hi) = if pi[0] <= pj[0] {
-                (xi1, xj1_plus)
-            } else {
-                (xj1, xi1_plus)
-            };
-            let (yi_lo, yi_hi) = if pi[1] <= pj[1] {
-                (yi1, yj1_plus)
-            } else {
-                (yj1, yi1_plus)
-            };
-
-            // Compute green area using 2D prefix sum (O(1) lookup)
-            let a = unsafe { *prefix.get_unchecked(yi_hi * stride + xi_hi) };
-            let b = unsafe { *prefix.get_unchecked(yi_lo * stride + xi_hi) };
-            let c = unsafe { *prefix.get_unchecked(yi_hi * stride + xi_lo) };
-            let d = unsafe { *prefix.get_unchecked(yi_lo * stride + xi_lo) };
-            let green_area = a + d - b - c;
-
-            if green_area == area {
-                max_area = area;
-            }
-        }
-    }
-
-    max_area
-}
-
 #[inline(always)]
 fn lower_bound(values: &[i32], target: i32) -> usize {
     values.partition_point(|&v| v < target)
@@ -347,7 +638,8 @@ fn expect_char(bytes: &[u8], len: usize, idx: &mut usize, expected: u8) -> Resul
 
 #[cfg(test)]
 mod tests {
-    use super::{both, max_area_any, max_area_within_green, parse_points};
+    use super::{both, largest_pair, parse_points, Rectangle, RectilinearPolygon};
+    use crate::test_rng::XorShift;
 
     const EXAMPLE: &str = "7,1\n\
 11,1\n\
@@ -361,14 +653,148 @@ mod tests {
     #[test]
     fn example_max_area() {
         let pts = parse_points(EXAMPLE).unwrap();
-        assert_eq!(max_area_any(&pts), 50);
+        assert_eq!(
+            largest_pair(&pts),
+            Rectangle {
+                min: [2, 1],
+                max: [11, 5]
+            }
+        );
     }
 
     #[test]
     fn example_part2() {
-        let pts = parse_points(EXAMPLE).unwrap();
-        let cov = super::build_coverage(&pts).unwrap();
-        assert_eq!(max_area_within_green(&pts, &cov), 24);
+        let polygon = RectilinearPolygon::parse(EXAMPLE).unwrap();
+        let best = polygon.largest_red_cornered();
+        assert_eq!(best.area(), 24);
+        assert!(polygon.contains_rect(best.min, best.max));
+        assert_eq!(
+            polygon.largest_rectangle(),
+            Rectangle {
+                min: [2, 3],
+                max: [11, 5]
+            }
+        );
+    }
+
+    #[test]
+    fn rectangle_searches_match_brute_force() {
+        // Every pair of red tiles on loops with random top and bottom
+        // profiles, in both orientations, each pair checked with
+        // `contains_rect`.
+        let mut rng = XorShift::new(0x2545_f491_4f6c_dd1d);
+        let mut next = |bound: u64| rng.below(bound) as i32;
+        for columns in [2, 10, 40, 120] {
+            let mut xs = vec![0];
+            let (mut bottom, mut top): (Vec<i32>, Vec<i32>) = (Vec::new(), Vec::new());
+            for _ in 0..columns {
+                xs.push(xs.last().unwrap() + next(6) + 1);
+                loop {
+                    let b = next(12);
+                    let t = b + 1 + next(15);
+                    // Neighbouring columns must differ and overlap.
+                    let fits = match (bottom.last(), top.last()) {
+                        (Some(&pb), Some(&pt)) => b != pb && t != pt && b < pt && t > pb,
+                        _ => true,
+                    };
+                    if fits {
+                        bottom.push(b);
+                        top.push(t);
+                        break;
+                    }
+                }
+            }
+            let mut loop_ = Vec::new();
+            for (i, &b) in bottom.iter().enumerate() {
+                loop_.push([xs[i], b]);
+                loop_.push([xs[i + 1], b]);
+            }
+            for (i, &t) in top.iter().enumerate().rev() {
+                loop_.push([xs[i + 1], t]);
+                loop_.push([xs[i], t]);
+            }
+            for flip in [1, -1] {
+                let flipped = loop_.iter().map(|&[x, y]| [x * flip, y]).collect();
+                let polygon = RectilinearPolygon::new(flipped).unwrap();
+                let reds = polygon.vertices();
+                let mut best = 0;
+                for (i, &a) in reds.iter().enumerate() {
+                    for &b in &reds[i + 1..] {
+                        let area = Rectangle::spanning(a, b).area();
+                        if area > best && polygon.contains_rect(a, b) {
+                            best = area;
+                        }
+                    }
+                }
+                let found = polygon.largest_red_cornered();
+                assert!(polygon.contains_rect(found.min, found.max));
+                assert_eq!(found.area(), best, "{} columns, flip {}", columns, flip);
+            }
+        }
+
+        // Every tile rectangle in and around a small loop with a notch.
+        let polygon =
+            RectilinearPolygon::parse("0,0\n6,0\n6,2\n4,2\n4,5\n9,5\n9,8\n1,8\n1,4\n0,4\n")
+                .unwrap();
+        let mut best = 0;
+        for x0 in -1..=10 {
+            for x1 in x0..=10 {
+                for y0 in -1..=9 {
+                    for y1 in y0..=9 {
+                        let area = Rectangle::spanning([x0, y0], [x1, y1]).area();
+                        if area > best && polygon.contains_rect([x0, y0], [x1, y1]) {
+                            best = area;
+                        }
+                    }
+                }
+            }
+        }
+        let any = polygon.largest_rectangle();
+        assert!(polygon.contains_rect(any.min, any.max));
+        assert_eq!(any.area(), best);
+    }
+
+    #[test]
+    fn polygon_queries() {
+        let polygon = RectilinearPolygon::parse(EXAMPLE).unwrap();
+        assert_eq!(polygon.vertices().len(), 8);
+        assert_eq!(polygon.area(), 46);
+        assert_eq!(polygon.perimeter(), 30);
+        assert_eq!(polygon.green_cells().map(|r| r.area()).sum::<u64>(), 46);
+        assert!(polygon.contains([2, 4]) && polygon.contains([10, 6]));
+        assert!(!polygon.contains([1, 4]) && !polygon.contains([8, 6]));
+        assert!(!polygon.contains([12, 1]) && !polygon.contains([7, 0]));
+        assert!(polygon.contains_rect([9, 5], [2, 3]));
+        assert!(polygon.contains_rect([3, 4], [8, 4]));
+        assert!(!polygon.contains_rect([7, 1], [11, 7]));
+        assert!(!polygon.contains_rect([2, 3], [12, 5]));
+    }
+
+    #[test]
+    fn polygon_validation_names_vertices() {
+        let err = |input: &str| RectilinearPolygon::parse(input).err().unwrap();
+        assert_eq!(
+            err("0,0\n4,0\n4,4\n0,4\n4,0\n"),
+            "vertices 1 and 4 are both at 4,0"
+        );
+        assert_eq!(
+            err("0,0\n4,0\n4,4\n"),
+            "edge 2-0 is not axis-aligned: 4,4 to 0,0"
+        );
+        assert_eq!(
+            err("0,0\n4,0\n4,4\n2,4\n2,-2\n0,-2\n"),
+            "loop intersects itself: edges 0-1 and 3-4 meet at 2,0"
+        );
+        // A vertex resting on another edge.
+        assert_eq!(
+            err("0,0\n4,0\n4,2\n2,2\n2,0\n2,-2\n0,-2\n"),
+            "loop intersects itself: edges 0-1 and 4-5 meet at 2,0"
+        );
+        // An edge doubling back over its predecessor.
+        assert_eq!(
+            err("0,0\n4,0\n2,0\n2,2\n0,2\n"),
+            "loop intersects itself: edges 0-1 and 1-2 meet at 2,0"
+        );
     }
 
     #[test]
@@ -2,240 +2,661 @@ pub static INPUT: &str = include_str!("../inputs/10.txt");
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
+use std::fmt;
+use std::ops::Range;
 
 /// Parsed machine data.
 struct Machine {
-    target: u16,
-    buttons: Vec<u16>,
+    /// Lights that must end up on, ascending.
+    target: Vec<u32>,
+    /// Lights toggled by each distinct button, ascending and flattened:
+    /// button `b` owns `wiring[ends[b - 1]..ends[b]]`.
+    wiring: Vec<u32>,
+    ends: Vec<u32>,
+    /// The distinct button behind each button of the input line, `u32::MAX`
+    /// for one wired to nothing.
+    inputs: Vec<u32>,
     joltages: Vec<u16>,
     n: usize,
+    /// Byte offset of the machine's `[` in the input, for error messages.
+    offset: usize,
 }
 
-pub fn part1(input: &str) -> Result<u64, String> {
-    let machines = parse(input)?;
-    #[cfg(feature = "parallel")]
-    {
-        Ok(machines.par_iter().map(|m| solve_lights(m) as u64).sum())
+impl Machine {
+    fn buttons(&self) -> usize {
+        self.ends.len()
     }
-    #[cfg(not(feature = "parallel"))]
-    {
-        Ok(machines.iter().map(|m| solve_lights(m) as u64).sum())
+
+    #[inline(always)]
+    fn button(&self, b: usize) -> &[u32] {
+        let start = if b == 0 { 0 } else { self.ends[b - 1] as usize };
+        &self.wiring[start..self.ends[b] as usize]
+    }
+
+    /// Spread presses of the distinct buttons over the input's buttons; a
+    /// repeated button's presses go to its first occurrence.
+    fn input_presses(&self, presses: &[u32]) -> Vec<u32> {
+        let mut seen = vec![false; self.buttons()];
+        let mut out = vec![0; self.inputs.len()];
+        for (slot, &b) in out.iter_mut().zip(&self.inputs) {
+            if let Some(first) = seen.get_mut(b as usize).filter(|s| !**s) {
+                *first = true;
+                *slot = presses[b as usize];
+            }
+        }
+        out
+    }
+
+    /// Why the lights (`joltage == false`) or joltages are unreachable once
+    /// elimination found the equations inconsistent.
+    fn contradiction(&self, joltage: bool) -> Unreachable {
+        let mut wired = vec![false; self.n];
+        for &light in &self.wiring {
+            wired[light as usize] = true;
+        }
+        let unwired = if joltage {
+            (0..self.n as u32).find(|&l| !wired[l as usize] && self.joltages[l as usize] > 0)
+        } else {
+            self.target.iter().copied().find(|&l| !wired[l as usize])
+        };
+        unwired.map_or(Unreachable::Inconsistent, Unreachable::Unwired)
+    }
+
+    /// Whether pressing the distinct buttons `presses` times lights exactly the
+    /// target lights (`joltage == false`) or meets every joltage requirement.
+    fn achieves(&self, presses: &[u32], joltage: bool) -> bool {
+        let mut counters = vec![0u64; self.n];
+        for (b, &count) in presses.iter().enumerate() {
+            for &light in self.button(b) {
+                counters[light as usize] += count as u64;
+            }
+        }
+        if joltage {
+            counters
+                .iter()
+                .zip(&self.joltages)
+                .all(|(&c, &j)| c == j as u64)
+        } else {
+            (0..self.n as u32)
+                .filter(|&light| counters[light as usize] % 2 == 1)
+                .eq(self.target.iter().copied())
+        }
     }
 }
 
-pub fn part2(input: &str) -> Result<u64, String> {
+/// Why a machine's lights or joltages cannot be reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unreachable {
+    /// The target needs this light (on, or with joltage), but no button is
+    /// wired to it.
+    Unwired(u32),
+    /// The buttons' equations contradict each other: over GF(2) for the
+    /// lights, over the rationals for the joltages.
+    Inconsistent,
+    /// Joltages only: the equations have solutions, but none presses every
+    /// button a whole, non-negative number of times.
+    Infeasible,
+}
+
+impl fmt::Display for Unreachable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unreachable::Unwired(light) => write!(f, "no button is wired to light {}", light),
+            Unreachable::Inconsistent => write!(f, "the buttons' equations contradict each other"),
+            Unreachable::Infeasible => {
+                write!(
+                    f,
+                    "no whole, non-negative number of presses fits the equations"
+                )
+            }
+        }
+    }
+}
+
+/// Why a solver returned no presses.
+enum Failure {
+    /// The target is unreachable; [`Policy`] decides what happens next.
+    Unreachable(Unreachable),
+    /// The machine is beyond what the solver can search or represent. Always
+    /// an error.
+    TooLarge(String),
+    /// Elimination broke one of its own invariants: a bug, not a property of
+    /// the machine. Always an error.
+    Internal(String),
+}
+
+impl From<Unreachable> for Failure {
+    fn from(reason: Unreachable) -> Self {
+        Failure::Unreachable(reason)
+    }
+}
+
+/// What to do with a machine whose target cannot be reached.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Policy {
+    /// Stop with an error naming the first such machine.
+    #[default]
+    Fail,
+    /// Leave it out of that part's total and list it in [`Totals::skipped`].
+    Skip,
+}
+
+/// Both parts' totals under a [`Policy`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Totals {
+    pub part1: u64,
+    pub part2: u64,
+    /// Targets left out of the totals, in input order.
+    pub skipped: Vec<Skipped>,
+}
+
+/// A machine target left out of a total.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Skipped {
+    /// 1-based machine number.
+    pub machine: usize,
+    /// 1-based input line of the machine.
+    pub line: usize,
+    /// `false` for the part 1 lights, `true` for the part 2 joltages.
+    pub joltage: bool,
+    pub reason: Unreachable,
+}
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "machine {} (line {}): {} unreachable: {}",
+            self.machine,
+            self.line,
+            if self.joltage { "joltages" } else { "lights" },
+            self.reason
+        )
+    }
+}
+
+/// Cheapest way to operate one machine, as press counts for the buttons in the
+/// order the input lists them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MachinePresses {
+    /// 1-based input line of the machine.
+    pub line: usize,
+    /// Lights each input button toggles.
+    pub buttons: Vec<Vec<u32>>,
+    /// Part 1: each button pressed at most once.
+    pub lights: Result<Vec<u32>, Unreachable>,
+    /// Part 2: presses that meet the joltage requirements.
+    pub joltages: Result<Vec<u32>, Unreachable>,
+}
+
+/// The press vectors behind both parts' totals, one entry per machine. Every
+/// vector is replayed against its machine before it is returned.
+pub fn presses(input: &str) -> Result<Vec<MachinePresses>, String> {
     let machines = parse(input)?;
+    let solve = |(idx, m): (usize, &Machine)| {
+        let split = |presses| match presses {
+            Ok(p) => Ok(Ok(p)),
+            Err(Failure::Unreachable(reason)) => Ok(Err(reason)),
+            Err(Failure::TooLarge(msg) | Failure::Internal(msg)) => {
+                Err(format!("machine {}: {}", idx + 1, msg))
+            }
+        };
+        let lights = split(solve_lights(m))?;
+        let joltages = split(solve_joltage(m))?;
+        for (presses, joltage) in [(&lights, false), (&joltages, true)] {
+            if presses.as_ref().is_ok_and(|p| !m.achieves(p, joltage)) {
+                return Err(format!(
+                    "machine {}: the {} presses found miss the target",
+                    idx + 1,
+                    if joltage { "joltage" } else { "light" }
+                ));
+            }
+        }
+        let buttons = m
+            .inputs
+            .iter()
+            .map(|&b| match b {
+                u32::MAX => Vec::new(),
+                b => m.button(b as usize).to_vec(),
+            })
+            .collect();
+        Ok(MachinePresses {
+            line: line_of(input, m.offset),
+            buttons,
+            lights: lights.map(|p| m.input_presses(&p)),
+            joltages: joltages.map(|p| m.input_presses(&p)),
+        })
+    };
     #[cfg(feature = "parallel")]
     {
-        Ok(machines.par_iter().map(|m| solve_joltage(m) as u64).sum())
+        machines.par_iter().enumerate().map(solve).collect()
     }
     #[cfg(not(feature = "parallel"))]
     {
-        Ok(machines.iter().map(|m| solve_joltage(m) as u64).sum())
+        machines.iter().enumerate().map(solve).collect()
     }
 }
 
-#[cfg(not(feature = "parallel"))]
+pub fn part1(input: &str) -> Result<u64, String> {
+    tally(input, Policy::Fail, true, false).map(|totals| totals.part1)
+}
+
+pub fn part2(input: &str) -> Result<u64, String> {
+    tally(input, Policy::Fail, false, true).map(|totals| totals.part2)
+}
+
 pub fn both(input: &str) -> Result<(u64, u64), String> {
+    tally(input, Policy::Fail, true, true).map(|totals| (totals.part1, totals.part2))
+}
+
+/// Both parts, with unreachable targets handled by `policy`.
+pub fn solve_with(input: &str, policy: Policy) -> Result<Totals, String> {
+    tally(input, policy, true, true)
+}
+
+/// Sum the requested parts over every machine, then apply `policy` to the
+/// unreachable targets in input order.
+fn tally(input: &str, policy: Policy, lights: bool, joltage: bool) -> Result<Totals, String> {
     let machines = parse(input)?;
-    let mut p1 = 0u64;
-    let mut p2 = 0u64;
-    for m in &machines {
-        p1 += solve_lights(m) as u64;
-        p2 += solve_joltage(m) as u64;
+    let cost = |presses: Result<Vec<u32>, Failure>| {
+        presses.map(|p| p.iter().map(|&c| c as u64).sum::<u64>())
+    };
+    let solve = |m: &Machine| {
+        (
+            if lights { cost(solve_lights(m)) } else { Ok(0) },
+            if joltage {
+                cost(solve_joltage(m))
+            } else {
+                Ok(0)
+            },
+        )
+    };
+    #[cfg(feature = "parallel")]
+    let costs: Vec<_> = machines.par_iter().map(solve).collect();
+    #[cfg(not(feature = "parallel"))]
+    let costs: Vec<_> = machines.iter().map(solve).collect();
+
+    let mut totals = Totals::default();
+    for (idx, (m, (c1, c2))) in machines.iter().zip(costs).enumerate() {
+        for (cost, total, joltage) in [
+            (c1, &mut totals.part1, false),
+            (c2, &mut totals.part2, true),
+        ] {
+            match cost {
+                Ok(c) => *total += c,
+                Err(Failure::TooLarge(msg) | Failure::Internal(msg)) => {
+                    return Err(format!(
+                        "machine {} (line {}): {}",
+                        idx + 1,
+                        line_of(input, m.offset),
+                        msg
+                    ));
+                }
+                Err(Failure::Unreachable(reason)) => {
+                    let skipped = Skipped {
+                        machine: idx + 1,
+                        line: line_of(input, m.offset),
+                        joltage,
+                        reason,
+                    };
+                    if policy == Policy::Fail {
+                        return Err(skipped.to_string());
+                    }
+                    totals.skipped.push(skipped);
+                }
+            }
+        }
     }
-    Ok((p1, p2))
+    Ok(totals)
 }
 
-#[cfg(feature = "parallel")]
-pub fn both(input: &str) -> Result<(u64, u64), String> {
-    let machines = parse(input)?;
-    let (p1, p2) = machines
-        .par_iter()
-        .map(|m| (solve_lights(m) as u64, solve_joltage(m) as u64))
-        .reduce(|| (0, 0), |(a1, a2), (b1, b2)| (a1 + b1, a2 + b2));
-    Ok((p1, p2))
+/// 1-based line of byte `offset` in `input`.
+fn line_of(input: &str, offset: usize) -> usize {
+    input.as_bytes()[..offset]
+        .iter()
+        .filter(|&&b| b == b'\n')
+        .count()
+        + 1
 }
 
 // =============================================================================
-// Part 1: Lights Out over GF(2) - optimized with u16 bitmasks
+// Part 1: Lights Out over GF(2) - bitmask rows, u16/u64 fast paths
 // =============================================================================
 
-fn solve_lights(m: &Machine) -> u32 {
-    if m.target == 0 {
-        return 0;
+/// Fixed-length bit set for the elimination rows. `u16` and `u64` cover
+/// machines with up to 16 or 64 lights and buttons; `BitSet` anything larger.
+trait Bits: Clone {
+    fn zeros(len: usize) -> Self;
+    fn get(&self, i: usize) -> bool;
+    fn toggle(&mut self, i: usize);
+    fn clear(&mut self);
+    fn xor(&mut self, other: &Self);
+    /// Parity of the bits set in both.
+    fn dot(&self, other: &Self) -> bool;
+    fn count(&self) -> u32;
+    fn first(&self) -> Option<usize>;
+}
+
+macro_rules! impl_bits {
+    ($($t:ty),*) => {$(
+        impl Bits for $t {
+            #[inline(always)]
+            fn zeros(_len: usize) -> Self {
+                0
+            }
+            #[inline(always)]
+            fn get(&self, i: usize) -> bool {
+                (self >> i) & 1 == 1
+            }
+            #[inline(always)]
+            fn toggle(&mut self, i: usize) {
+                *self ^= 1 << i;
+            }
+            #[inline(always)]
+            fn clear(&mut self) {
+                *self = 0;
+            }
+            #[inline(always)]
+            fn xor(&mut self, other: &Self) {
+                *self ^= other;
+            }
+            #[inline(always)]
+            fn dot(&self, other: &Self) -> bool {
+                (self & other).count_ones() & 1 == 1
+            }
+            #[inline(always)]
+            fn count(&self) -> u32 {
+                self.count_ones()
+            }
+            #[inline(always)]
+            fn first(&self) -> Option<usize> {
+                (*self != 0).then(|| self.trailing_zeros() as usize)
+            }
+        }
+    )*};
+}
+
+impl_bits!(u16, u64);
+
+#[derive(Clone)]
+struct BitSet(Vec<u64>);
+
+impl Bits for BitSet {
+    fn zeros(len: usize) -> Self {
+        BitSet(vec![0; len.div_ceil(64)])
+    }
+    fn get(&self, i: usize) -> bool {
+        self.0[i / 64].get(i % 64)
+    }
+    fn toggle(&mut self, i: usize) {
+        self.0[i / 64].toggle(i % 64);
+    }
+    fn clear(&mut self) {
+        self.0.fill(0);
+    }
+    fn xor(&mut self, other: &Self) {
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            *a ^= b;
+        }
+    }
+    fn dot(&self, other: &Self) -> bool {
+        let shared: u32 = self
+            .0
+            .iter()
+            .zip(&other.0)
+            .map(|(a, b)| (a & b).count_ones())
+            .sum();
+        shared & 1 == 1
+    }
+    fn count(&self) -> u32 {
+        self.0.iter().map(|w| w.count_ones()).sum()
+    }
+    fn first(&self) -> Option<usize> {
+        let word = self.0.iter().position(|&w| w != 0)?;
+        Some(word * 64 + self.0[word].trailing_zeros() as usize)
+    }
+}
+
+/// Fewest presses that light the target: each distinct button pressed 0 or 1
+/// times.
+fn solve_lights(m: &Machine) -> Result<Vec<u32>, Failure> {
+    let width = m.n.max(m.buttons());
+    if width <= 16 {
+        solve_lights_with::<u16>(m)
+    } else if width <= 64 {
+        solve_lights_with::<u64>(m)
+    } else {
+        solve_lights_with::<BitSet>(m)
+    }
+}
+
+/// Eliminate, then try every assignment of the free buttons, or search the
+/// reachable light patterns when there are fewer of those. `B` must hold
+/// `max(lights, buttons)` bits.
+fn solve_lights_with<B: Bits>(m: &Machine) -> Result<Vec<u32>, Failure> {
+    let num_buttons = m.buttons();
+    if m.target.is_empty() {
+        return Ok(vec![0; num_buttons]);
     }
-    let num_buttons = m.buttons.len();
     if num_buttons == 0 {
-        return u32::MAX;
+        return Err(m.contradiction(false).into());
     }
 
     // Build matrix: mat[light] = bitmask of buttons that affect it.
-    let mut mat = [0u16; 16];
-    for (btn_idx, &btn) in m.buttons.iter().enumerate() {
-        let mut bits = btn;
-        while bits != 0 {
-            let light = bits.trailing_zeros() as usize;
-            mat[light] |= 1 << btn_idx;
-            bits &= bits - 1;
+    let mut mat = vec![B::zeros(num_buttons); m.n];
+    for btn_idx in 0..num_buttons {
+        for &light in m.button(btn_idx) {
+            mat[light as usize].toggle(btn_idx);
         }
     }
+    let mut target = B::zeros(m.n);
+    for &light in &m.target {
+        target.toggle(light as usize);
+    }
 
     // Gaussian elimination over GF(2).
-    let mut target = m.target;
-    let mut pivot_mask = 0u16;
+    let mut is_pivot = vec![false; num_buttons];
     let mut rank = 0;
 
-    for col in 0..num_buttons {
-        let col_bit = 1u16 << col;
-        let mut pivot = None;
-        for row in rank..m.n {
-            if mat[row] & col_bit != 0 {
-                pivot = Some(row);
-                break;
-            }
-        }
-        let Some(prow) = pivot else { continue };
+    for (col, pivot) in is_pivot.iter_mut().enumerate() {
+        let Some(prow) = (rank..m.n).find(|&row| mat[row].get(col)) else {
+            continue;
+        };
 
         mat.swap(rank, prow);
         // Swap target bits.
-        let t_rank = (target >> rank) & 1;
-        let t_prow = (target >> prow) & 1;
-        target = (target & !(1 << rank) & !(1 << prow)) | (t_prow << rank) | (t_rank << prow);
+        if target.get(rank) != target.get(prow) {
+            target.toggle(rank);
+            target.toggle(prow);
+        }
 
-        let pivot_row = mat[rank];
+        let pivot_row = mat[rank].clone();
+        let pivot_target = target.get(rank);
 
-        for row in 0..m.n {
-            if row != rank && mat[row] & col_bit != 0 {
-                mat[row] ^= pivot_row;
-                let t1 = (target >> row) & 1;
-                let t2 = (target >> rank) & 1;
-                target = (target & !(1 << row)) | ((t1 ^ t2) << row);
+        for (row, bits) in mat.iter_mut().enumerate() {
+            if row != rank && bits.get(col) {
+                bits.xor(&pivot_row);
+                if pivot_target {
+                    target.toggle(row);
+                }
             }
         }
 
-        pivot_mask |= col_bit;
+        *pivot = true;
         rank += 1;
     }
+    // Rows past the rank are all zero; a target bit left there is 0 = 1.
+    if (rank..m.n).any(|row| target.get(row)) {
+        return Err(m.contradiction(false).into());
+    }
 
-    let num_free = num_buttons - rank;
-    let free_mask = !pivot_mask & ((1u16 << num_buttons) - 1);
-
-    let mut free_cols = [0u8; 16];
-    let mut free_count = 0usize;
-    for col in 0..num_buttons {
-        if free_mask & (1 << col) != 0 {
-            free_cols[free_count] = col as u8;
-            free_count += 1;
+    let free_cols: Vec<usize> = (0..num_buttons).filter(|&col| !is_pivot[col]).collect();
+    let num_free = free_cols.len();
+    // Enumerating visits `2^free` assignments, the search `2^rank` patterns.
+    if num_free > rank.min(MAX_SEARCH_BITS) {
+        if rank <= MAX_SEARCH_BITS {
+            return Ok(fewest_columns(&mat[..rank], &target, rank, num_buttons));
         }
+        return Err(Failure::TooLarge(format!(
+            "{} free buttons over {} independent lights are too many to search",
+            num_free, rank
+        )));
     }
 
-    let mut pivot_cols = [0u8; 16];
-    let mut other_masks = [0u16; 16];
-    for row in 0..rank {
-        let row_val = mat[row];
-        let pc = row_val.trailing_zeros() as usize;
-        pivot_cols[row] = pc as u8;
-        other_masks[row] = row_val & !(1 << pc);
+    let mut pivot_cols = Vec::with_capacity(rank);
+    let mut other_masks = Vec::with_capacity(rank);
+    for row in mat.iter_mut().take(rank) {
+        let pc = row.first().unwrap_or(0);
+        row.toggle(pc);
+        pivot_cols.push(pc);
+        other_masks.push(row.clone());
     }
 
     let mut best = u32::MAX;
-    for free_bits in 0u16..(1 << num_free) {
-        let mut solution = 0u16;
-        for i in 0..num_free {
+    let mut solution = B::zeros(num_buttons);
+    let mut best_solution = B::zeros(num_buttons);
+    for free_bits in 0u64..(1 << num_free) {
+        solution.clear();
+        for (i, &col) in free_cols.iter().enumerate() {
             if (free_bits >> i) & 1 == 1 {
-                solution |= 1 << (free_cols[i] as usize);
+                solution.toggle(col);
             }
         }
 
         for row in (0..rank).rev() {
-            let pivot_col = pivot_cols[row] as usize;
-            let rhs = (target >> row) & 1;
-            let xor_sum = (other_masks[row] & solution).count_ones() & 1;
-            if (rhs as u32) ^ xor_sum == 1 {
-                solution |= 1 << pivot_col;
+            if target.get(row) ^ other_masks[row].dot(&solution) {
+                solution.toggle(pivot_cols[row]);
             }
         }
 
-        let cost = solution.count_ones();
+        let cost = solution.count();
         if cost < best {
             best = cost;
+            best_solution = solution.clone();
         }
     }
 
-    best
+    Ok((0..num_buttons)
+        .map(|b| best_solution.get(b) as u32)
+        .collect())
+}
+
+/// Most free buttons enumerated, and largest rank searched breadth-first, so
+/// either way a machine costs at most `2^20` candidates.
+const MAX_SEARCH_BITS: usize = 20;
+
+/// Breadth-first search over the `2^rank` patterns the buttons can reach, for
+/// machines with more free buttons than independent lights. `rows` and
+/// `target` are the reduced rows, so each button is the `rank`-bit pattern of
+/// its column and a pivot button is a single bit. A shortest path presses no
+/// button twice.
+fn fewest_columns<B: Bits>(rows: &[B], target: &B, rank: usize, num_buttons: usize) -> Vec<u32> {
+    let mut columns = vec![0u32; num_buttons];
+    let mut goal = 0u32;
+    for (r, row) in rows.iter().enumerate() {
+        for (col, bits) in columns.iter_mut().enumerate() {
+            if row.get(col) {
+                *bits |= 1 << r;
+            }
+        }
+        if target.get(r) {
+            goal |= 1 << r;
+        }
+    }
+
+    // `via[state]` is the button pressed last on a shortest path to `state`.
+    let mut via = vec![u32::MAX; 1 << rank];
+    let mut queue = vec![0u32];
+    let mut head = 0;
+    while via[goal as usize] == u32::MAX && goal != 0 {
+        let state = queue[head];
+        head += 1;
+        for (b, &bits) in columns.iter().enumerate() {
+            let next = state ^ bits;
+            if next != 0 && via[next as usize] == u32::MAX {
+                via[next as usize] = b as u32;
+                queue.push(next);
+            }
+        }
+    }
+
+    let mut presses = vec![0; num_buttons];
+    let mut state = goal;
+    while state != 0 {
+        let b = via[state as usize] as usize;
+        presses[b] = 1;
+        state ^= columns[b];
+    }
+    presses
 }
 
 // =============================================================================
 // Part 2: Integer counter - iterative search
 // =============================================================================
 
-fn solve_joltage(m: &Machine) -> u32 {
+/// Fewest presses that meet the joltage requirements, per distinct button.
+fn solve_joltage(m: &Machine) -> Result<Vec<u32>, Failure> {
+    let num_buttons = m.buttons();
     if m.joltages.iter().all(|&v| v == 0) {
-        return 0;
+        return Ok(vec![0; num_buttons]);
     }
-    let num_buttons = m.buttons.len();
     if num_buttons == 0 {
-        return u32::MAX;
+        return Err(m.contradiction(true).into());
     }
 
-    // Compute upper bounds per button.
-    let mut bounds = [u16::MAX; 16];
-    let mut mat = [[0i32; 17]; 16];
-    for (col, &btn) in m.buttons.iter().enumerate() {
-        let mut bits = btn;
-        while bits != 0 {
-            let row = bits.trailing_zeros() as usize;
+    // Compute upper bounds per button. Row `r` of the augmented matrix is
+    // `mat[r * width..(r + 1) * width]`, right-hand side last.
+    let width = num_buttons + 1;
+    let mut bounds = vec![u16::MAX; num_buttons];
+    let mut mat = vec![0i32; m.n * width];
+    for col in 0..num_buttons {
+        for &row in m.button(col) {
+            let row = row as usize;
             bounds[col] = bounds[col].min(unsafe { *m.joltages.get_unchecked(row) });
-            mat[row][col] = 1;
-            bits &= bits - 1;
+            mat[row * width + col] = 1;
         }
     }
     for row in 0..m.n {
-        mat[row][num_buttons] = unsafe { *m.joltages.get_unchecked(row) } as i32;
+        mat[row * width + num_buttons] = unsafe { *m.joltages.get_unchecked(row) } as i32;
     }
 
     // Gaussian elimination.
-    let mut pivot_col_for_row = [usize::MAX; 16];
+    let mut pivot_col_for_row = Vec::with_capacity(m.n.min(num_buttons));
+    let mut pivot = vec![0i32; width];
+    let mut wide = vec![0i64; width];
+    let overflow = || Failure::TooLarge("the joltage equations overflow 32-bit integers".into());
     let mut pivot_row = 0;
 
     for col in 0..num_buttons {
-        let mut found = None;
-        for r in pivot_row..m.n {
-            if mat[r][col] != 0 {
-                found = Some(r);
-                break;
+        let Some(pr) = (pivot_row..m.n).find(|&r| mat[r * width + col] != 0) else {
+            continue;
+        };
+
+        if pr != pivot_row {
+            for c in 0..width {
+                mat.swap(pivot_row * width + c, pr * width + c);
             }
         }
-        let Some(pr) = found else { continue };
-
-        mat.swap(pivot_row, pr);
-        pivot_col_for_row[pivot_row] = col;
-        let pivot_val = mat[pivot_row][col];
+        pivot_col_for_row.push(col);
+        pivot.copy_from_slice(&mat[pivot_row * width..(pivot_row + 1) * width]);
+        let pivot_val = pivot[col];
 
-        for r in 0..m.n {
-            if r == pivot_row || mat[r][col] == 0 {
+        for (r, row) in mat.chunks_exact_mut(width).enumerate() {
+            if r == pivot_row || row[col] == 0 {
                 continue;
             }
-            let factor = mat[r][col];
-            for c in 0..=num_buttons {
-                mat[r][c] = mat[r][c] * pivot_val - factor * mat[pivot_row][c];
-            }
-            let mut g = 0i32;
-            for c in 0..=num_buttons {
-                let v = mat[r][c].abs();
-                if v == 0 {
-                    continue;
-                }
-                g = gcd(g, v);
-                if g == 1 {
-                    break;
+            // `i32 * i32 - i32 * i32` always fits an `i64`; only the reduced
+            // row can overflow.
+            let factor = row[col] as i64;
+            let mut g = 0i64;
+            for ((w, &v), &p) in wide.iter_mut().zip(row.iter()).zip(&pivot) {
+                *w = v as i64 * pivot_val as i64 - factor * p as i64;
+                if *w != 0 && g != 1 {
+                    g = gcd(g, *w);
                 }
             }
-            if g > 1 {
-                for c in 0..=num_buttons {
-                    mat[r][c] /= g;
-                }
+            for (v, &w) in row.iter_mut().zip(&wide) {
+                *v = i32::try_from(w / g.max(1)).map_err(|_| overflow())?;
             }
         }
 
@@ -243,90 +664,78 @@ fn solve_joltage(m: &Machine) -> u32 {
     }
 
     let rank = pivot_row;
-
-    // Collect free columns sorted by bound.
-    let mut is_pivot = [false; 16];
-    for r in 0..rank {
-        is_pivot[pivot_col_for_row[r]] = true;
+    if (rank..m.n).any(|r| mat[r * width + num_buttons] != 0) {
+        return Err(m.contradiction(true).into());
     }
 
-    let mut free_data = [(0usize, 0u16); 16];
-    let mut num_free = 0;
-    for col in 0..num_buttons {
-        if !is_pivot[col] {
-            free_data[num_free] = (col, bounds[col]);
-            num_free += 1;
-        }
+    // Collect free columns sorted by bound.
+    let mut is_pivot = vec![false; num_buttons];
+    for &col in &pivot_col_for_row {
+        is_pivot[col] = true;
     }
-    free_data[..num_free].sort_by_key(|&(_, b)| b);
 
-    let free_cols: [usize; 16] = {
-        let mut arr = [0; 16];
-        for i in 0..num_free {
-            arr[i] = free_data[i].0;
-        }
-        arr
-    };
-    let free_bounds: [u16; 16] = {
-        let mut arr = [0; 16];
-        for i in 0..num_free {
-            arr[i] = free_data[i].1;
-        }
-        arr
-    };
+    let mut free_data: Vec<(usize, u16)> = (0..num_buttons)
+        .filter(|&col| !is_pivot[col])
+        .map(|col| (col, bounds[col]))
+        .collect();
+    free_data.sort_by_key(|&(_, b)| b);
+    let num_free = free_data.len();
 
     // Map column index -> free variable index (if free).
-    let mut col_to_free = [u8::MAX; 16];
-    for (idx, &col) in free_cols[..num_free].iter().enumerate() {
-        col_to_free[col] = idx as u8;
+    let mut col_to_free = vec![u32::MAX; num_buttons];
+    for (idx, &(col, _)) in free_data.iter().enumerate() {
+        col_to_free[col] = idx as u32;
     }
 
     // Precompute back-substitution coefficients for faster evaluation.
     // Store sparse representation in terms of *free indices* (not columns).
-    // Format: pivot_val, rhs, bound, num_terms, [(free_idx, coeff); 16]
-    let mut backsub = [(1i32, 0i32, 0i32, 0u8, [(0u8, 0i32); 16]); 16];
-    for r in 0..rank {
-        let pc = pivot_col_for_row[r];
-        let pivot_val = mat[r][pc];
-        let rhs = mat[r][num_buttons];
-        let mut terms = [(0usize, 0i32); 16];
-        let mut num_terms = 0;
-        for c in 0..num_buttons {
-            if c != pc && mat[r][c] != 0 {
+    let mut backsub = Vec::with_capacity(rank);
+    let mut terms: Vec<(u32, i32)> = Vec::new();
+    for (r, &pc) in pivot_col_for_row.iter().enumerate() {
+        let row = &mat[r * width..(r + 1) * width];
+        let start = terms.len();
+        for (c, &coeff) in row[..num_buttons].iter().enumerate() {
+            if c != pc && coeff != 0 {
                 let free_idx = unsafe { *col_to_free.get_unchecked(c) };
-                if free_idx == u8::MAX {
-                    return u32::MAX;
+                // Elimination clears every pivot column outside its own row.
+                if free_idx == u32::MAX {
+                    return Err(Failure::Internal(format!(
+                        "internal error: joltage pivot row {} still uses pivot button {}",
+                        r, c
+                    )));
                 }
-                terms[num_terms] = (free_idx as usize, mat[r][c]);
-                num_terms += 1;
+                terms.push((free_idx, coeff));
             }
         }
-        let mut packed = [(0u8, 0i32); 16];
-        for i in 0..num_terms {
-            packed[i] = (terms[i].0 as u8, terms[i].1);
-        }
-        backsub[r] = (
-            pivot_val,
-            rhs,
-            bounds[pc] as i32,
-            num_terms as u8,
-            packed,
+        // Every partial sum `eval_fast` and the reconstruction below can
+        // form stays within `i32`.
+        let reach = terms[start..].iter().fold(
+            row[num_buttons].unsigned_abs() as u64,
+            |acc, &(fi, coeff)| acc + coeff.unsigned_abs() as u64 * free_data[fi as usize].1 as u64,
         );
+        if reach > i32::MAX as u64 {
+            return Err(overflow());
+        }
+        backsub.push(BackSub {
+            pivot_val: row[pc],
+            rhs: row[num_buttons],
+            bound: bounds[pc] as i32,
+            terms: start..terms.len(),
+        });
     }
 
     let mut best = u32::MAX;
 
     // Compute max values for each free variable (sorted by bound).
-    let mut max_vals = [0u32; 16];
-    for i in 0..num_free {
-        max_vals[i] = free_bounds[i] as u32;
-    }
+    let max_vals: Vec<u32> = free_data.iter().map(|&(_, b)| b as u32).collect();
 
-    let mut free_vals = [0i32; 16];
+    let mut free_vals = vec![0i32; num_free];
+    let mut best_free = vec![0i32; num_free];
     match num_free {
         0 => {
-            if let Some(cost) = eval_fast(0, &free_vals, 0, &backsub, rank, best) {
+            if let Some(cost) = eval_fast(&free_vals, 0, &backsub, &terms, best) {
                 best = cost;
+                best_free.copy_from_slice(&free_vals);
             }
         }
         1 => {
@@ -336,8 +745,9 @@ fn solve_joltage(m: &Machine) -> u32 {
                     break;
                 }
                 free_vals[0] = v0;
-                if let Some(cost) = eval_fast(1, &free_vals, v0 as u32, &backsub, rank, best) {
+                if let Some(cost) = eval_fast(&free_vals, v0 as u32, &backsub, &terms, best) {
                     best = cost;
+                    best_free.copy_from_slice(&free_vals);
                 }
             }
         }
@@ -355,8 +765,9 @@ fn solve_joltage(m: &Machine) -> u32 {
                         break;
                     }
                     free_vals[1] = v1;
-                    if let Some(cost) = eval_fast(2, &free_vals, partial, &backsub, rank, best) {
+                    if let Some(cost) = eval_fast(&free_vals, partial, &backsub, &terms, best) {
                         best = cost;
+                        best_free.copy_from_slice(&free_vals);
                     }
                 }
             }
@@ -383,10 +794,9 @@ fn solve_joltage(m: &Machine) -> u32 {
                     for v2 in 0..=max2_allowed {
                         let partial = (partial01 + v2) as u32;
                         free_vals[2] = v2;
-                        if let Some(cost) =
-                            eval_fast(3, &free_vals, partial, &backsub, rank, best)
-                        {
+                        if let Some(cost) = eval_fast(&free_vals, partial, &backsub, &terms, best) {
                             best = cost;
+                            best_free.copy_from_slice(&free_vals);
                         }
                     }
                 }
@@ -394,15 +804,14 @@ fn solve_joltage(m: &Machine) -> u32 {
         }
         _ => {
             // Fallback to a generic depth-first search.
-            let mut partial_costs = [0u32; 16];
+            let mut partial_costs = vec![0u32; num_free];
             let mut idx = 0usize;
             loop {
                 if idx == num_free {
                     let partial = partial_costs[idx - 1];
-                    if let Some(cost) =
-                        eval_fast(num_free, &free_vals, partial, &backsub, rank, best)
-                    {
+                    if let Some(cost) = eval_fast(&free_vals, partial, &backsub, &terms, best) {
                         best = cost;
+                        best_free.copy_from_slice(&free_vals);
                     }
                     if idx == 0 {
                         break;
@@ -447,28 +856,49 @@ fn solve_joltage(m: &Machine) -> u32 {
         }
     }
 
-    best
+    if best == u32::MAX {
+        return Err(Unreachable::Infeasible.into());
+    }
+    let mut presses = vec![0u32; num_buttons];
+    for (&(col, _), &v) in free_data.iter().zip(&best_free) {
+        presses[col] = v as u32;
+    }
+    for (row, &pc) in backsub.iter().zip(&pivot_col_for_row) {
+        let mut sum = row.rhs;
+        for &(fi, coeff) in &terms[row.terms.clone()] {
+            sum -= coeff * best_free[fi as usize];
+        }
+        presses[pc] = (sum / row.pivot_val) as u32;
+    }
+    Ok(presses)
+}
+
+/// A pivot row solved for its pivot button: `pivot_val * x = rhs - sum(coeff *
+/// free)`, with the `(free index, coeff)` pairs at `terms` in the shared list.
+struct BackSub {
+    pivot_val: i32,
+    rhs: i32,
+    bound: i32,
+    terms: Range<usize>,
 }
 
 #[inline(always)]
 fn eval_fast(
-    _num_free: usize,
-    free_vals: &[i32; 16],
+    free_vals: &[i32],
     total_free: u32,
-    backsub: &[(i32, i32, i32, u8, [(u8, i32); 16]); 16],
-    rank: usize,
+    backsub: &[BackSub],
+    terms: &[(u32, i32)],
     best: u32,
 ) -> Option<u32> {
     let mut total = total_free;
 
-    for r in 0..rank {
-        let (pivot_val, rhs, bound, num_terms, ref terms) = backsub[r];
-        let mut sum = rhs;
-        for i in 0..(num_terms as usize) {
-            let (fi, coeff) = unsafe { *terms.get_unchecked(i) };
+    for row in backsub {
+        let mut sum = row.rhs;
+        for &(fi, coeff) in unsafe { terms.get_unchecked(row.terms.clone()) } {
             sum -= coeff * unsafe { *free_vals.get_unchecked(fi as usize) };
         }
 
+        let pivot_val = row.pivot_val;
         let val = if pivot_val == 1 {
             sum
         } else if pivot_val == -1 {
@@ -480,7 +910,7 @@ fn eval_fast(
             sum / pivot_val
         };
 
-        if val < 0 || val > bound {
+        if val < 0 || val > row.bound {
             return None;
         }
 
@@ -494,13 +924,13 @@ fn eval_fast(
 }
 
 #[inline(always)]
-fn gcd(mut a: i32, mut b: i32) -> i32 {
+fn gcd(mut a: i64, mut b: i64) -> i64 {
     while b != 0 {
         let t = b;
         b = a % b;
         a = t;
     }
-    a.abs().max(1)
+    a.abs()
 }
 
 // =============================================================================
@@ -520,20 +950,23 @@ fn parse(input: &str) -> Result<Vec<Machine>, String> {
         if i >= len {
             break;
         }
+        let offset = i;
         i += 1;
+        let number = machines.len() + 1;
 
-        let mut target = 0u16;
+        let mut target = Vec::new();
         let mut n = 0;
         while i < len && bytes[i] != b']' {
             if bytes[i] == b'#' {
-                target |= 1 << n;
+                target.push(n as u32);
             }
             n += 1;
             i += 1;
         }
         i += 1;
 
-        let mut buttons: Vec<u16> = Vec::with_capacity(16);
+        let mut wiring: Vec<u32> = Vec::with_capacity(64);
+        let mut ends: Vec<u32> = Vec::with_capacity(16);
         while i < len {
             while i < len && bytes[i] != b'(' && bytes[i] != b'{' && bytes[i] != b'\n' {
                 i += 1;
@@ -541,24 +974,39 @@ fn parse(input: &str) -> Result<Vec<Machine>, String> {
             if i >= len || bytes[i] == b'{' || bytes[i] == b'\n' {
                 break;
             }
+            let open = i;
             i += 1;
 
-            let mut mask = 0u16;
+            let start = wiring.len();
             while i < len && bytes[i] != b')' {
                 if bytes[i].is_ascii_digit() {
                     let mut val = 0usize;
                     while i < len && bytes[i].is_ascii_digit() {
-                        val = val * 10 + (bytes[i] - b'0') as usize;
+                        val = val
+                            .saturating_mul(10)
+                            .saturating_add((bytes[i] - b'0') as usize);
                         i += 1;
                     }
-                    mask |= 1 << val;
+                    if val >= n {
+                        let end = input[open..].find(')').map_or(len, |e| open + e + 1);
+                        return Err(format!(
+                            "machine {}: button {} toggles light {}, but only {} lights are declared",
+                            number,
+                            &input[open..end],
+                            val,
+                            n
+                        ));
+                    }
+                    wiring.push(val as u32);
                 } else {
                     i += 1;
                 }
             }
-            if mask != 0 {
-                buttons.push(mask);
-            }
+            let lights = &mut wiring[start..];
+            lights.sort_unstable();
+            let kept = dedup_sorted(lights);
+            wiring.truncate(start + kept);
+            ends.push(wiring.len() as u32);
             if i < len {
                 i += 1;
             }
@@ -585,24 +1033,65 @@ fn parse(input: &str) -> Result<Vec<Machine>, String> {
                 i += 1;
             }
         }
+        if joltages.len() != n {
+            return Err(format!(
+                "machine {}: expected {} joltage requirements, found {}",
+                number,
+                n,
+                joltages.len()
+            ));
+        }
 
-        buttons.sort_unstable();
-        buttons.dedup();
+        // Order buttons by their light mask read as a number (highest light
+        // first) and drop duplicates, as the elimination's pivot order depends
+        // on it.
+        let button = |b: usize| {
+            let start = if b == 0 { 0 } else { ends[b - 1] as usize };
+            &wiring[start..ends[b] as usize]
+        };
+        let mut order: Vec<usize> = (0..ends.len()).filter(|&b| !button(b).is_empty()).collect();
+        order.sort_unstable_by(|&x, &y| button(x).iter().rev().cmp(button(y).iter().rev()));
+        let mut inputs = vec![u32::MAX; ends.len()];
+        let mut sorted = Vec::with_capacity(wiring.len());
+        let mut sorted_ends: Vec<u32> = Vec::with_capacity(order.len());
+        for (k, &b) in order.iter().enumerate() {
+            if k == 0 || button(b) != button(order[k - 1]) {
+                sorted.extend_from_slice(button(b));
+                sorted_ends.push(sorted.len() as u32);
+            }
+            inputs[b] = sorted_ends.len() as u32 - 1;
+        }
 
         machines.push(Machine {
             target,
-            buttons,
+            wiring: sorted,
+            ends: sorted_ends,
+            inputs,
             joltages,
             n,
+            offset,
         });
     }
 
     Ok(machines)
 }
 
+/// Move the distinct values of a sorted slice to its front; returns how many.
+fn dedup_sorted(values: &mut [u32]) -> usize {
+    let mut kept = 0;
+    for idx in 0..values.len() {
+        if kept == 0 || values[idx] != values[kept - 1] {
+            values[kept] = values[idx];
+            kept += 1;
+        }
+    }
+    kept
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_rng::XorShift;
 
     const EXAMPLE: &str = r#"[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
 [...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}
@@ -624,4 +1113,164 @@ mod tests {
         assert_eq!(p1, 7);
         assert_eq!(p2, 33);
     }
+
+    #[test]
+    fn test_presses() {
+        let machines = presses(EXAMPLE).unwrap();
+        let total = |p: &Result<Vec<u32>, Unreachable>| p.as_ref().unwrap().iter().sum::<u32>();
+        assert_eq!(machines.iter().map(|m| total(&m.lights)).sum::<u32>(), 7);
+        assert_eq!(machines.iter().map(|m| total(&m.joltages)).sum::<u32>(), 33);
+        let first = &machines[0];
+        assert_eq!(first.buttons[1], vec![1, 3]);
+        // Replay the joltage presses.
+        let mut counters = [0u32; 4];
+        for (lights, &count) in first.buttons.iter().zip(first.joltages.as_ref().unwrap()) {
+            for &light in lights {
+                counters[light as usize] += count;
+            }
+        }
+        assert_eq!(counters, [3, 5, 4, 7]);
+
+        // Repeated buttons are reported in input order, at their first occurrence.
+        let machines = presses("[##] (1,0) () (0,1) (0) {3,3}").unwrap();
+        assert_eq!(
+            machines[0].buttons,
+            vec![vec![0, 1], vec![], vec![0, 1], vec![0]]
+        );
+        assert_eq!(machines[0].lights, Ok(vec![1, 0, 0, 0]));
+        assert_eq!(machines[0].joltages, Ok(vec![3, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let input = "[#.] (1) {0,1}\n\n[#.] (0,1) {1,2}\n[##] (0,1) (0) {1,2}\n[.#] (1) {0,3}";
+        assert_eq!(
+            part1(input).err().unwrap(),
+            "machine 1 (line 1): lights unreachable: no button is wired to light 0"
+        );
+        assert_eq!(
+            part2(input).err().unwrap(),
+            "machine 2 (line 3): joltages unreachable: the buttons' equations contradict each other"
+        );
+
+        let totals = solve_with(input, Policy::Skip).unwrap();
+        assert_eq!((totals.part1, totals.part2), (2, 4));
+        let reasons: Vec<(usize, usize, bool, Unreachable)> = totals
+            .skipped
+            .iter()
+            .map(|s| (s.machine, s.line, s.joltage, s.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (1, 1, false, Unreachable::Unwired(0)),
+                (2, 3, false, Unreachable::Inconsistent),
+                (2, 3, true, Unreachable::Inconsistent),
+                (3, 4, true, Unreachable::Infeasible),
+            ]
+        );
+        assert_eq!(
+            presses(input).unwrap()[2].joltages,
+            Err(Unreachable::Infeasible)
+        );
+    }
+
+    #[test]
+    fn test_wide_machines() {
+        // 20 lights take the u64 path, 70 the general bit set.
+        for n in [20, 70] {
+            // One button per light plus one that toggles them all.
+            let mut line = format!("[{}]", "#".repeat(n));
+            for light in 0..n {
+                line += &format!(" ({})", light);
+            }
+            let all: Vec<String> = (0..n).map(|light| light.to_string()).collect();
+            line += &format!(" ({}) {{{}}}", all.join(","), vec!["2"; n].join(","));
+            assert_eq!(both(&line).unwrap(), (1, 2));
+        }
+    }
+
+    #[test]
+    fn test_many_free_buttons() {
+        // Every pair of lights plus every single light: more free buttons
+        // than independent lights, so the reachable patterns are searched.
+        for (n, expected) in [(7, 4), (10, 5), (30, 15)] {
+            let mut line = format!("[{}]", "#".repeat(n));
+            for a in 0..n {
+                line += &format!(" ({})", a);
+                for b in a + 1..n {
+                    line += &format!(" ({},{})", a, b);
+                }
+            }
+            line += &format!(" {{{}}}", vec!["1"; n].join(","));
+            if n <= MAX_SEARCH_BITS {
+                assert_eq!(part1(&line).unwrap(), expected);
+            } else {
+                assert_eq!(
+                    part1(&line).err().unwrap(),
+                    "machine 1 (line 1): 435 free buttons over 30 independent lights are too many to search"
+                );
+            }
+        }
+
+        // 26 lights, one button each, plus a chain of pair buttons: too many
+        // lights for the search, so the pairs are enumerated up to the cap.
+        for (pairs, expected) in [(20, Ok(16)), (21, Err(()))] {
+            let mut line = format!("[{}]", "#".repeat(26));
+            for a in 0..26 {
+                line += &format!(" ({})", a);
+            }
+            for a in 0..pairs {
+                line += &format!(" ({},{})", a, a + 1);
+            }
+            line += &format!(" {{{}}}", vec!["1"; 26].join(","));
+            match expected {
+                Ok(presses) => assert_eq!(part1(&line).unwrap(), presses),
+                Err(()) => assert_eq!(
+                    part1(&line).err().unwrap(),
+                    "machine 1 (line 1): 21 free buttons over 26 independent lights are too many to search"
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_joltage_overflow() {
+        // 30 dense buttons and large joltages: elimination outgrows `i32`.
+        let mut rng = XorShift::new(12345);
+        let mut next = || rng.next_u64() as usize;
+        let n = 30;
+        let mut line = format!("[{}]", ".".repeat(n));
+        for _ in 0..n {
+            let lights: Vec<String> = (0..n)
+                .filter(|_| next() % 2 == 0)
+                .map(|l| l.to_string())
+                .collect();
+            line += &format!(" ({})", lights.join(","));
+        }
+        let joltages: Vec<String> = (0..n)
+            .map(|_| (60000 + next() % 5000).to_string())
+            .collect();
+        line += &format!(" {{{}}}", joltages.join(","));
+        assert_eq!(
+            part2(&line).err().unwrap(),
+            "machine 1 (line 1): the joltage equations overflow 32-bit integers"
+        );
+        assert_eq!(
+            solve_with(&line, Policy::Skip).err().unwrap(),
+            "machine 1 (line 1): the joltage equations overflow 32-bit integers"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("[.##.] (3) (1,4) {1,2,3,4}").err().unwrap(),
+            "machine 1: button (1,4) toggles light 4, but only 4 lights are declared"
+        );
+        assert_eq!(
+            parse("[.#] (0) (1) {1,1}\n[.#] (0) (1) {1}").err().unwrap(),
+            "machine 2: expected 2 joltage requirements, found 1"
+        );
+    }
 }
@@ -0,0 +1,254 @@
+//! Union-find over `0..len` with union by size, shared by the graph-heavy days
+//! (8 and anything clustering-like).
+//!
+//! `find` compresses paths, except while a checkpoint is open: rollback needs
+//! every link it undoes to still be in place, so then only union by size keeps
+//! the trees shallow (depth stays logarithmic).
+
+/// Integer types usable as element indices and set sizes. Narrower types halve
+/// the memory of large sets; `new` panics if `len` does not fit.
+pub trait Index: Copy + Eq {
+    const MAX: usize;
+    fn from_usize(value: usize) -> Self;
+    fn to_usize(self) -> usize;
+}
+
+macro_rules! impl_index {
+    ($($t:ty),*) => {$(
+        impl Index for $t {
+            const MAX: usize = <$t>::MAX as usize;
+            #[inline(always)]
+            fn from_usize(value: usize) -> Self {
+                value as $t
+            }
+            #[inline(always)]
+            fn to_usize(self) -> usize {
+                self as usize
+            }
+        }
+    )*};
+}
+
+impl_index!(u16, u32, u64, usize);
+
+/// Position in the undo log returned by [`DisjointSet::checkpoint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+/// Disjoint sets of `0..len`.
+#[derive(Clone, Debug)]
+pub struct DisjointSet<I = u32> {
+    parents: Vec<I>,
+    /// Set size, valid at roots only.
+    sizes: Vec<I>,
+    components: usize,
+    /// `(attached root, new root)` for every union since the oldest open
+    /// checkpoint.
+    log: Vec<(I, I)>,
+    logging: bool,
+}
+
+impl<I: Index> DisjointSet<I> {
+    /// `len` singleton sets.
+    pub fn new(len: usize) -> Self {
+        // Sizes share the index type, so the largest set (`len`) must fit too.
+        assert!(len <= I::MAX, "{} elements do not fit the index type", len);
+        Self {
+            parents: (0..len).map(I::from_usize).collect(),
+            sizes: vec![I::from_usize(1); len],
+            components: len,
+            log: Vec::new(),
+            logging: false,
+        }
+    }
+
+    /// Number of elements.
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Number of disjoint sets.
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    /// Representative of `x`'s set.
+    #[inline]
+    pub fn find(&mut self, x: usize) -> usize {
+        let root = self.root(x);
+        if !self.logging {
+            let mut cur = x;
+            while cur != root {
+                let next = self.parents[cur].to_usize();
+                self.parents[cur] = I::from_usize(root);
+                cur = next;
+            }
+        }
+        root
+    }
+
+    /// Representative of `x`'s set without compressing the path.
+    #[inline]
+    pub fn root(&self, x: usize) -> usize {
+        let mut cur = x;
+        loop {
+            let parent = self.parents[cur].to_usize();
+            if parent == cur {
+                return cur;
+            }
+            cur = parent;
+        }
+    }
+
+    /// Merge the sets of `a` and `b`; `false` if they were already one set.
+    #[inline]
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut ra, mut rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return false;
+        }
+        if self.sizes[ra].to_usize() < self.sizes[rb].to_usize() {
+            std::mem::swap(&mut ra, &mut rb);
+        }
+        let merged = self.sizes[ra].to_usize() + self.sizes[rb].to_usize();
+        self.parents[rb] = I::from_usize(ra);
+        self.sizes[ra] = I::from_usize(merged);
+        self.components -= 1;
+        if self.logging {
+            self.log.push((I::from_usize(rb), I::from_usize(ra)));
+        }
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Size of `x`'s set.
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.sizes[root].to_usize()
+    }
+
+    /// One representative per set, ascending.
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).filter(|&x| self.parents[x].to_usize() == x)
+    }
+
+    /// Members of every set, largest first; equal sizes are ordered by their
+    /// smallest member.
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let mut by_root: Vec<Vec<usize>> = vec![Vec::new(); self.len()];
+        for x in 0..self.len() {
+            let root = self.find(x);
+            by_root[root].push(x);
+        }
+        let mut groups: Vec<Vec<usize>> = by_root.into_iter().filter(|g| !g.is_empty()).collect();
+        groups.sort_by(|x, y| y.len().cmp(&x.len()).then_with(|| x[0].cmp(&y[0])));
+        groups
+    }
+
+    /// Sizes of the `k` largest sets, largest first (fewer if there are fewer
+    /// sets).
+    pub fn top_sizes(&self, k: usize) -> Vec<usize> {
+        let mut top: Vec<usize> = Vec::with_capacity(k + 1);
+        for root in self.roots() {
+            let size = self.sizes[root].to_usize();
+            if top.len() == k && top.last().is_none_or(|&min| size <= min) {
+                continue;
+            }
+            let pos = top.partition_point(|&s| s >= size);
+            top.insert(pos, size);
+            top.truncate(k);
+        }
+        top
+    }
+
+    /// Start (or extend) recording unions so they can be undone with
+    /// [`DisjointSet::rollback`]. Path compression pauses until
+    /// [`DisjointSet::commit`].
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        self.logging = true;
+        Checkpoint(self.log.len())
+    }
+
+    /// Undo every union made since `checkpoint`, newest first.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        while self.log.len() > checkpoint.0 {
+            let Some((child, root)) = self.log.pop() else {
+                break;
+            };
+            let (child, root) = (child.to_usize(), root.to_usize());
+            let remaining = self.sizes[root].to_usize() - self.sizes[child].to_usize();
+            self.parents[child] = I::from_usize(child);
+            self.sizes[root] = I::from_usize(remaining);
+            self.components += 1;
+        }
+    }
+
+    /// Keep every union made so far, drop the undo log and resume path
+    /// compression. Earlier checkpoints become invalid.
+    pub fn commit(&mut self) {
+        self.log.clear();
+        self.logging = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DisjointSet;
+
+    #[test]
+    fn unions_sizes_and_groups() {
+        let mut set: DisjointSet<u16> = DisjointSet::new(8);
+        assert!(set.union(0, 1));
+        assert!(set.union(2, 3));
+        assert!(set.union(1, 3));
+        assert!(!set.union(0, 2));
+        assert!(set.union(5, 6));
+        assert_eq!(set.components(), 4);
+        assert_eq!(set.size_of(3), 4);
+        assert!(set.same(0, 3) && !set.same(4, 5));
+        assert_eq!(set.top_sizes(3), vec![4, 2, 1]);
+        assert_eq!(set.top_sizes(10), vec![4, 2, 1, 1]);
+        assert_eq!(
+            set.groups(),
+            vec![vec![0, 1, 2, 3], vec![5, 6], vec![4], vec![7]]
+        );
+    }
+
+    #[test]
+    fn sizes_fit_at_the_type_boundary() {
+        let mut set: DisjointSet<u16> = DisjointSet::new(u16::MAX as usize);
+        for x in 1..set.len() {
+            set.union(x - 1, x);
+        }
+        assert_eq!(set.top_sizes(1), vec![u16::MAX as usize]);
+        assert_eq!(set.size_of(0), u16::MAX as usize);
+        assert!(std::panic::catch_unwind(|| DisjointSet::<u16>::new(1 << 16)).is_err());
+    }
+
+    #[test]
+    fn rollback_restores_earlier_state() {
+        let mut set: DisjointSet = DisjointSet::new(6);
+        set.union(0, 1);
+        let before = set.checkpoint();
+        set.union(1, 2);
+        let middle = set.checkpoint();
+        set.union(3, 4);
+        set.union(2, 4);
+        assert_eq!(set.components(), 2);
+        set.rollback(middle);
+        assert_eq!(set.groups(), vec![vec![0, 1, 2], vec![3], vec![4], vec![5]]);
+        set.rollback(before);
+        assert_eq!(set.top_sizes(2), vec![2, 1]);
+        assert_eq!(set.components(), 5);
+        set.commit();
+        set.union(4, 5);
+        assert_eq!(set.find(5), set.find(4));
+    }
+}
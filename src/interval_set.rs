@@ -0,0 +1,304 @@
+//! Sorted set of disjoint closed integer intervals, shared by the range-heavy
+//! days (2 and 5).
+//!
+//! Intervals are stored as a sorted `Vec` of inclusive `(start, end)` pairs
+//! that never overlap or touch, so membership is a binary search and set
+//! algebra is a linear merge.
+
+use std::fmt;
+
+/// Integer types usable as interval endpoints.
+pub trait Endpoint: Copy + Ord + fmt::Debug {
+    const MIN: Self;
+    const MAX: Self;
+    /// `self + 1`, or `None` at `MAX`.
+    fn succ(self) -> Option<Self>;
+    /// `self - 1`, or `None` at `MIN`.
+    fn pred(self) -> Option<Self>;
+    /// Number of values in `lo..=hi` (requires `lo <= hi`).
+    fn span(lo: Self, hi: Self) -> u128;
+}
+
+macro_rules! impl_endpoint {
+    ($($t:ty),*) => {$(
+        impl Endpoint for $t {
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+            #[inline]
+            fn succ(self) -> Option<Self> {
+                self.checked_add(1)
+            }
+            #[inline]
+            fn pred(self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+            #[inline]
+            fn span(lo: Self, hi: Self) -> u128 {
+                (hi as i128 - lo as i128) as u128 + 1
+            }
+        }
+    )*};
+}
+
+impl_endpoint!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// Set of integers stored as disjoint, non-adjacent closed intervals.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: Endpoint> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    /// Build from arbitrary (possibly overlapping, unsorted) inclusive ranges.
+    /// Ranges with `start > end` are ignored.
+    pub fn from_ranges(ranges: impl IntoIterator<Item = (T, T)>) -> Self {
+        let mut ranges: Vec<(T, T)> = ranges.into_iter().filter(|&(s, e)| s <= e).collect();
+        ranges.sort_unstable_by_key(|&(start, _)| start);
+        Self::from_sorted(ranges)
+    }
+
+    /// Coalesce ranges already sorted by start.
+    fn from_sorted(ranges: impl IntoIterator<Item = (T, T)>) -> Self {
+        let mut merged: Vec<(T, T)> = Vec::new();
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(current) if touches(current.1, start) => {
+                    if end > current.1 {
+                        current.1 = end;
+                    }
+                }
+                _ => merged.push((start, end)),
+            }
+        }
+        Self { ranges: merged }
+    }
+
+    /// Number of disjoint intervals.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The intervals in ascending order.
+    pub fn as_slice(&self) -> &[(T, T)] {
+        &self.ranges
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.ranges.iter().copied()
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let idx = self.ranges.partition_point(|&(start, _)| start <= value);
+        idx > 0 && value <= self.ranges[idx - 1].1
+    }
+
+    /// Index of the interval holding `value`, if any.
+    pub fn position(&self, value: T) -> Option<usize> {
+        let idx = self.ranges.partition_point(|&(start, _)| start <= value);
+        (idx > 0 && value <= self.ranges[idx - 1].1).then(|| idx - 1)
+    }
+
+    /// Total number of integers covered.
+    pub fn count_covered(&self) -> u128 {
+        self.ranges.iter().map(|&(s, e)| T::span(s, e)).sum()
+    }
+
+    /// Add `lo..=hi`, merging with any overlapping or adjacent intervals.
+    pub fn insert(&mut self, lo: T, hi: T) {
+        if lo > hi {
+            return;
+        }
+        // Intervals strictly before `lo` and not adjacent to it stay put.
+        let first = self.ranges.partition_point(|&(_, e)| !touches(e, lo));
+        // Intervals starting after `hi + 1` stay put.
+        let last = self.ranges.partition_point(|&(s, _)| touches(hi, s));
+        let (mut new_lo, mut new_hi) = (lo, hi);
+        if first < last {
+            new_lo = new_lo.min(self.ranges[first].0);
+            new_hi = new_hi.max(self.ranges[last - 1].1);
+        }
+        self.ranges.splice(first..last, [(new_lo, new_hi)]);
+    }
+
+    /// Remove `lo..=hi`, splitting intervals that straddle its ends.
+    pub fn remove(&mut self, lo: T, hi: T) {
+        if lo > hi {
+            return;
+        }
+        let first = self.ranges.partition_point(|&(_, e)| e < lo);
+        let last = self.ranges.partition_point(|&(s, _)| s <= hi);
+        if first >= last {
+            return;
+        }
+        let (head_start, _) = self.ranges[first];
+        let (_, tail_end) = self.ranges[last - 1];
+        let mut keep = Vec::with_capacity(2);
+        if head_start < lo {
+            keep.push((head_start, lo.pred().unwrap_or(lo)));
+        }
+        if tail_end > hi {
+            keep.push((hi.succ().unwrap_or(hi), tail_end));
+        }
+        self.ranges.splice(first..last, keep);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut a = self.ranges.iter().copied().peekable();
+        let mut b = other.ranges.iter().copied().peekable();
+        let merged = std::iter::from_fn(|| match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) if x.0 <= y.0 => a.next(),
+            (Some(_), Some(_)) => b.next(),
+            (Some(_), None) => a.next(),
+            (None, _) => b.next(),
+        });
+        Self::from_sorted(merged)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (a, b) = (&self.ranges, &other.ranges);
+        let (mut i, mut j) = (0, 0);
+        let mut out = Vec::new();
+        while i < a.len() && j < b.len() {
+            let lo = a[i].0.max(b[j].0);
+            let hi = a[i].1.min(b[j].1);
+            if lo <= hi {
+                out.push((lo, hi));
+            }
+            if a[i].1 < b[j].1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { ranges: out }
+    }
+
+    /// Values in `self` but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement(T::MIN, T::MAX))
+    }
+
+    /// Values of `lo..=hi` not covered by the set.
+    pub fn complement(&self, lo: T, hi: T) -> Self {
+        Self {
+            ranges: self.gaps(lo, hi).collect(),
+        }
+    }
+
+    /// Uncovered sub-ranges of `lo..=hi`, in ascending order.
+    pub fn gaps(&self, lo: T, hi: T) -> Gaps<'_, T> {
+        let next = self.ranges.partition_point(|&(_, e)| e < lo);
+        Gaps {
+            ranges: &self.ranges,
+            next,
+            cursor: (lo <= hi).then_some(lo),
+            hi,
+        }
+    }
+}
+
+impl<T: Endpoint> FromIterator<(T, T)> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        Self::from_ranges(iter)
+    }
+}
+
+impl<T: Endpoint> fmt::Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.ranges.iter().map(|(s, e)| s..=e))
+            .finish()
+    }
+}
+
+/// Iterator returned by [`IntervalSet::gaps`].
+pub struct Gaps<'a, T> {
+    ranges: &'a [(T, T)],
+    next: usize,
+    /// First value not yet accounted for, `None` once past `hi`.
+    cursor: Option<T>,
+    hi: T,
+}
+
+impl<T: Endpoint> Iterator for Gaps<'_, T> {
+    type Item = (T, T);
+
+    fn next(&mut self) -> Option<(T, T)> {
+        loop {
+            let cursor = self.cursor?;
+            let Some(&(start, end)) = self.ranges.get(self.next).filter(|r| r.0 <= self.hi) else {
+                self.cursor = None;
+                return Some((cursor, self.hi));
+            };
+            self.next += 1;
+            self.cursor = end.succ().filter(|&c| c <= self.hi);
+            if start > cursor {
+                return Some((cursor, start.pred().unwrap_or(start)));
+            }
+        }
+    }
+}
+
+/// Whether an interval ending at `end` overlaps or is adjacent to one starting
+/// at `start`.
+#[inline]
+fn touches<T: Endpoint>(end: T, start: T) -> bool {
+    match end.succ() {
+        Some(next) => start <= next,
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntervalSet;
+
+    #[test]
+    fn insert_and_remove_keep_intervals_canonical() {
+        let mut set: IntervalSet<u64> =
+            IntervalSet::from_ranges([(3, 5), (10, 14), (16, 20), (12, 18)]);
+        assert_eq!(set.as_slice(), &[(3, 5), (10, 20)]);
+        set.insert(6, 8);
+        assert_eq!(set.as_slice(), &[(3, 8), (10, 20)]);
+        set.insert(9, 9);
+        assert_eq!(set.as_slice(), &[(3, 20)]);
+        set.remove(5, 6);
+        set.remove(20, 30);
+        assert_eq!(set.as_slice(), &[(3, 4), (7, 19)]);
+        assert_eq!(set.count_covered(), 15);
+        assert!(set.contains(4) && !set.contains(5) && set.contains(19));
+        assert_eq!(
+            set.gaps(0, 25).collect::<Vec<_>>(),
+            vec![(0, 2), (5, 6), (20, 25)]
+        );
+    }
+
+    #[test]
+    fn set_algebra_and_extreme_bounds() {
+        let a: IntervalSet<i32> = [(-10, 0), (5, 9)].into_iter().collect();
+        let b: IntervalSet<i32> = [(-3, 6)].into_iter().collect();
+        assert_eq!(a.union(&b).as_slice(), &[(-10, 9)]);
+        assert_eq!(a.intersection(&b).as_slice(), &[(-3, 0), (5, 6)]);
+        assert_eq!(a.difference(&b).as_slice(), &[(-10, -4), (7, 9)]);
+        assert_eq!(b.complement(-5, 10).as_slice(), &[(-5, -4), (7, 10)]);
+
+        let mut full: IntervalSet<u8> = IntervalSet::from_ranges([(0, 255)]);
+        assert_eq!(full.count_covered(), 256);
+        assert_eq!(full.complement(0, 255).len(), 0);
+        full.remove(0, 0);
+        full.remove(255, 255);
+        assert_eq!(full.as_slice(), &[(1, 254)]);
+        assert_eq!(
+            full.gaps(0, 255).collect::<Vec<_>>(),
+            vec![(0, 0), (255, 255)]
+        );
+    }
+}
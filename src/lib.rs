@@ -10,6 +10,11 @@ pub mod day09;
 pub mod day10;
 pub mod day11;
 pub mod day12;
+pub mod disjoint_set;
+pub mod interval_set;
+pub mod render;
+#[cfg(test)]
+mod test_rng;
 
 pub const IMPLEMENTED_DAYS: [u8; 12] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
 
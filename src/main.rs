@@ -2,13 +2,66 @@ use advent_25::{solve, Part, IMPLEMENTED_DAYS};
 use std::collections::HashMap;
 use std::env;
 use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
+const USAGE: &str = "Usage: advent-25 <day|all> [part|mode] [options] < input.txt\n       \
+advent-25 <day> [part] INPUT|- [options]\n       \
+advent-25 render 9 [INPUT|-] [--anywhere] [--out PATH]\n\
+Options:\n  --pick K    day 3: pick K digits per line instead of solving parts 1/2\n  \
+--waves     day 4: print each removal wave as an ASCII frame\n  \
+--engine E  day 4: removal engine, 'queue' (default) or 'bits' (bit-parallel)\n  \
+--explain   day 5: list the input lines of the ranges that make each ID fresh\n  \
+--breakdown day 6: show each problem's columns, operator, operands and results\n  \
+--density   day 7: color each cell by how many beams enter it (log scale)\n  \
+--pairs K   day 8: connect the K closest pairs for part 1 and report the circuits\n  \
+--metric M  day 8: 'euclidean' (default), 'manhattan' or 'chebyshev'\n  \
+--anywhere  day 9: largest red or green rectangle with corners anywhere\n  \
+--presses   day 10: list the buttons each machine presses for both parts\n  \
+--skip      day 10: leave machines that cannot reach their target out of the totals\n  \
+--out PATH  write the visualisation to PATH (day 4 --waves: animated PNG;\n              \
+day 7 --density: PNG, or SVG if PATH ends in .svg; render: SVG)";
+
 fn main() {
-    let mut args = env::args().skip(1);
+    let (positional, mut options) = parse_args(env::args().skip(1));
+    let mut args = positional.into_iter().peekable();
+    if args.next_if(|arg| arg == "render").is_some() {
+        let day_selection = parse_day(args.next());
+        let DaySelection::One(day @ 9) = day_selection else {
+            eprintln!("Only day 9 can be rendered.\n{}", USAGE);
+            std::process::exit(1);
+        };
+        let source = args.next();
+        if args.next().is_some() {
+            eprintln!("Unexpected extra arguments.\n{}", USAGE);
+            std::process::exit(1);
+        }
+        let out = options.out.take();
+        options.check_applies_to(day_selection);
+        let mut input_owned = String::new();
+        let input = read_input(day, source.as_deref(), &mut input_owned);
+        match render_day9(input, options.anywhere, out.as_deref()) {
+            Ok(text) => print!("{}", text),
+            Err(err) => {
+                eprintln!("Error (day {}): {}", day, err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
     let day_selection = parse_day(args.next());
-    let part_or_mode = args.next();
+    let mut part_or_mode = args.next();
     let show_timing = env::var("ADVENT_HIDE_TIMING").is_err();
+    // A single day may name its input after the part; with the part left out,
+    // anything that is not a part is the input.
+    let mut source = None;
+    if let DaySelection::One(_) = day_selection {
+        source = args.next();
+        let is_part = |raw: &String| Part::from_str(raw).is_ok();
+        if source.is_none() && part_or_mode.as_ref().is_some_and(|raw| !is_part(raw)) {
+            source = part_or_mode.take();
+        }
+    }
     let (part, run_mode) = match day_selection {
         DaySelection::All => {
             let mode = parse_run_mode(&part_or_mode);
@@ -23,11 +76,10 @@ fn main() {
     };
 
     if args.next().is_some() {
-        eprintln!(
-            "Unexpected extra arguments.\nUsage: advent-25 <day|all> [part|mode] < input.txt"
-        );
+        eprintln!("Unexpected extra arguments.\n{}", USAGE);
         std::process::exit(1);
     }
+    options.check_applies_to(day_selection);
 
     match day_selection {
         DaySelection::All => {
@@ -60,14 +112,20 @@ fn main() {
         }
         DaySelection::One(day) => {
             let mut input_owned = String::new();
-            let input = input_for_day(day, &mut input_owned);
-            let outcome = run_day(day, part, input, show_timing);
+            let input = read_input(day, source.as_deref(), &mut input_owned);
+            let outcome = run_day(day, part, input, show_timing, &options);
             print_outcome(&outcome, false);
         }
     }
 }
 
-fn run_day(day: u8, part: Part, input: &str, show_timing: bool) -> RunOutcome {
+fn run_day(
+    day: u8,
+    part: Part,
+    input: &str,
+    show_timing: bool,
+    options: &RunOptions,
+) -> RunOutcome {
     let profiling = env::var_os("PPROF").is_some() && day == 2;
     let loops: usize = env::var("PPROF_LOOPS")
         .ok()
@@ -85,7 +143,7 @@ fn run_day(day: u8, part: Part, input: &str, show_timing: bool) -> RunOutcome {
     let timer_start = show_timing.then(Instant::now);
     let mut answer = String::new();
     for iter in 0..loops {
-        let res = match solve(day, part, input) {
+        let res = match solve_with_options(day, part, input, options) {
             Ok(answer) => answer,
             Err(err) => {
                 eprintln!("Error (day {}): {}", day, err);
@@ -117,7 +175,13 @@ fn run_all_days(run_mode: RunMode, show_timing: bool) -> Vec<RunOutcome> {
         for &day in &IMPLEMENTED_DAYS {
             let input = embedded_input_for_day(day)
                 .unwrap_or_else(|| unreachable!("IMPLEMENTED_DAYS contained unexpected day {}", day));
-            outcomes.push(run_day(day, Part::Both, input, show_timing));
+            outcomes.push(run_day(
+                day,
+                Part::Both,
+                input,
+                show_timing,
+                &RunOptions::default(),
+            ));
         }
         return outcomes;
     }
@@ -131,7 +195,7 @@ fn run_all_days(run_mode: RunMode, show_timing: bool) -> Vec<RunOutcome> {
             .map(|&day| {
                 let input = embedded_input_for_day(day)
                     .unwrap_or_else(|| unreachable!("IMPLEMENTED_DAYS contained unexpected day {}", day));
-                run_day(day, Part::Both, input, show_timing)
+                run_day(day, Part::Both, input, show_timing, &RunOptions::default())
             })
             .collect();
         outcomes.sort_by_key(|outcome| outcome.day);
@@ -144,12 +208,485 @@ fn run_all_days(run_mode: RunMode, show_timing: bool) -> Vec<RunOutcome> {
         for &day in &IMPLEMENTED_DAYS {
             let input = embedded_input_for_day(day)
                 .unwrap_or_else(|| unreachable!("IMPLEMENTED_DAYS contained unexpected day {}", day));
-            outcomes.push(run_day(day, Part::Both, input, show_timing));
+            outcomes.push(run_day(
+                day,
+                Part::Both,
+                input,
+                show_timing,
+                &RunOptions::default(),
+            ));
         }
         outcomes
     }
 }
 
+/// Dispatch to a day-specific entry point when an option changes what is solved,
+/// falling back to the regular part 1/part 2 solver otherwise.
+fn solve_with_options(
+    day: u8,
+    part: Part,
+    input: &str,
+    options: &RunOptions,
+) -> Result<String, String> {
+    match day {
+        3 => {
+            if let Some(k) = options.pick {
+                return advent_25::day03::solve_pick(input, k)
+                    .map(|total| format!("Pick {}: {}", k, total));
+            }
+        }
+        4 => {
+            if options.waves {
+                return render_day4_waves(input, options.out.as_deref());
+            }
+            if let Some(engine) = options.engine {
+                let (p1, p2) = advent_25::day04::both_with_engine(input, engine)?;
+                return Ok(format_parts(part, p1, p2));
+            }
+        }
+        5 if options.explain => return explain_day5(input),
+        6 if options.breakdown => return breakdown_day6(input),
+        7 if options.density => return render_day7_density(input, options.out.as_deref()),
+        8 => {
+            let metric = options.metric.unwrap_or_default();
+            if let Some(pairs) = options.pairs {
+                return report_day8(input, pairs, metric);
+            }
+            if options.metric.is_some() {
+                let (p1, p2) = advent_25::day08::solve_with(input, 1000, metric)?;
+                return Ok(format_parts(part, p1, p2));
+            }
+        }
+        9 if options.anywhere => {
+            let polygon = advent_25::day09::RectilinearPolygon::parse(input)?;
+            let best = polygon.largest_rectangle();
+            return Ok(format!(
+                "Largest green rectangle: {} tiles, {},{} to {},{}",
+                best.area(),
+                best.min[0],
+                best.min[1],
+                best.max[0],
+                best.max[1]
+            ));
+        }
+        10 if options.presses => return presses_day10(input),
+        10 if options.skip => return skip_day10(input, part),
+        _ => {}
+    }
+    solve(day, part, input)
+}
+
+/// One line per day 6 problem with its segment (1-based columns), operator,
+/// operands in both reading orders and both results.
+fn breakdown_day6(input: &str) -> Result<String, String> {
+    let problems = advent_25::day06::breakdown::<u128>(input)?;
+    let join = |values: Vec<String>| values.join(", ");
+    let mut out = String::new();
+    for (idx, problem) in problems.iter().enumerate() {
+        let rows = join(problem.row_operands.iter().map(|v| v.to_string()).collect());
+        let cols = join(
+            problem
+                .col_operands
+                .iter()
+                .map(|(col, v)| format!("{}@{}", v, col + 1))
+                .collect(),
+        );
+        out.push_str(&format!(
+            "Problem {} (columns {}-{}, {}): rows [{}] = {}; columns [{}] = {}\n",
+            idx + 1,
+            problem.columns.start + 1,
+            problem.columns.end,
+            problem.op.symbol(),
+            rows,
+            problem.row_result,
+            cols,
+            problem.col_result
+        ));
+    }
+    let (p1, p2) = advent_25::day06::totals(&problems)?;
+    out.push_str(&format!("Part 1: {}\nPart 2: {}", p1, p2));
+    Ok(out)
+}
+
+/// Format answers the way `solve` does for the requested part.
+fn format_parts(part: Part, p1: impl std::fmt::Display, p2: impl std::fmt::Display) -> String {
+    match part {
+        Part::One => p1.to_string(),
+        Part::Two => p2.to_string(),
+        Part::Both => format!("Part 1: {}\nPart 2: {}", p1, p2),
+    }
+}
+
+/// One line per ingredient ID naming the ranges that make it fresh.
+fn explain_day5(input: &str) -> Result<String, String> {
+    let audits = advent_25::day05::audit(input)?;
+    let mut out = String::new();
+    for audit in &audits {
+        if audit.is_fresh() {
+            let lines: Vec<String> = audit.lines.iter().map(|l| l.to_string()).collect();
+            out.push_str(&format!(
+                "ID {}: fresh (ranges on lines {})\n",
+                audit.id,
+                lines.join(", ")
+            ));
+        } else {
+            out.push_str(&format!("ID {}: spoiled\n", audit.id));
+        }
+    }
+    let fresh = audits.iter().filter(|a| a.is_fresh()).count();
+    out.push_str(&format!("Fresh: {} of {}", fresh, audits.len()));
+    Ok(out)
+}
+
+/// ASCII frame per removal wave (removed rolls drawn as `x`), optionally also
+/// written as an animated PNG.
+fn render_day4_waves(input: &str, out: Option<&Path>) -> Result<String, String> {
+    use advent_25::day04::Waves;
+    use advent_25::render::{encode_png, IndexedImage};
+
+    const PALETTE: [[u8; 3]; 3] = [[250, 248, 240], [70, 70, 80], [220, 60, 40]];
+    let mut waves = Waves::new(input)?;
+    let (width, height) = (waves.width(), waves.height());
+    let scale = if width.max(height) <= 256 { 2 } else { 1 };
+    let snapshot = |waves: &Waves, marked: &[(usize, usize)]| {
+        let mut image = IndexedImage::new(width * scale, height * scale, PALETTE.to_vec());
+        for row in 0..height {
+            for col in 0..width {
+                if waves.is_roll(row, col) {
+                    image.fill_cell(col, row, scale, 1);
+                }
+            }
+        }
+        for &(row, col) in marked {
+            image.fill_cell(col, row, scale, 2);
+        }
+        image
+    };
+
+    let mut text = String::new();
+    let mut frames = vec![snapshot(&waves, &[])];
+    let mut total = 0usize;
+    let mut count = 0usize;
+    while let Some(wave) = waves.next() {
+        count += 1;
+        total += wave.len();
+        text.push_str(&format!("Wave {}: {} removed\n", count, wave.len()));
+        text.push_str(&waves.frame(&wave));
+        text.push('\n');
+        if out.is_some() {
+            frames.push(snapshot(&waves, &wave));
+        }
+    }
+    text.push_str(&format!(
+        "Stable after {} waves, {} removed:\n{}",
+        count,
+        total,
+        waves.frame(&[])
+    ));
+
+    if let Some(path) = out {
+        frames.push(snapshot(&waves, &[]));
+        std::fs::write(path, encode_png(&frames, 120))
+            .map_err(|err| format!("failed to write {}: {}", path.display(), err))?;
+        eprintln!("Wrote {}", path.display());
+    }
+    Ok(text)
+}
+
+/// SVG of the day 9 loop: green cells from the coverage grid, the red-tile
+/// loop through tile centres, and the part 1 (blue, dashed) and part 2
+/// (orange) rectangles, plus the arbitrary-corner one (white, dotted) with
+/// `anywhere`. Returns the SVG, or a summary once it is written to `out`.
+fn render_day9(input: &str, anywhere: bool, out: Option<&Path>) -> Result<String, String> {
+    use advent_25::day09::{Rectangle, RectilinearPolygon};
+    let polygon = RectilinearPolygon::parse(input)?;
+    let mut highlights = vec![
+        (
+            "Part 1",
+            polygon.largest_red_pair(),
+            "#4aa3ff",
+            "stroke-dasharray=\"8 4\" ",
+        ),
+        ("Part 2", polygon.largest_red_cornered(), "#ffa020", ""),
+    ];
+    if anywhere {
+        highlights.push((
+            "Any corners",
+            polygon.largest_rectangle(),
+            "#ffffff",
+            "stroke-dasharray=\"2 3\" ",
+        ));
+    }
+
+    let bounds = polygon.vertices().iter().fold(
+        Rectangle::spanning(polygon.vertices()[0], polygon.vertices()[0]),
+        |r, &v| {
+            Rectangle::spanning(
+                [r.min[0].min(v[0]), r.min[1].min(v[1])],
+                [r.max[0].max(v[0]), r.max[1].max(v[1])],
+            )
+        },
+    );
+    let (w, h) = (
+        bounds.max[0] as i64 - bounds.min[0] as i64 + 1,
+        bounds.max[1] as i64 - bounds.min[1] as i64 + 1,
+    );
+    // A 2% margin, and at most 1000 pixels along the longer side.
+    let margin = (w.max(h) / 50).max(1);
+    let px = 1000.0 / (w.max(h) + 2 * margin) as f64;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" \
+         viewBox=\"{} {} {} {}\">\n\
+         <rect x=\"{2}\" y=\"{3}\" width=\"{4}\" height=\"{5}\" fill=\"#14181e\"/>\n\
+         <g fill=\"#2f8f4e\">\n",
+        (w + 2 * margin) as f64 * px,
+        (h + 2 * margin) as f64 * px,
+        bounds.min[0] as i64 - margin,
+        bounds.min[1] as i64 - margin,
+        w + 2 * margin,
+        h + 2 * margin
+    );
+    for cell in polygon.green_cells() {
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>\n",
+            cell.min[0],
+            cell.min[1],
+            cell.max[0] as i64 - cell.min[0] as i64 + 1,
+            cell.max[1] as i64 - cell.min[1] as i64 + 1
+        ));
+    }
+    svg.push_str("</g>\n<polygon fill=\"none\" stroke=\"#e03c3c\" stroke-width=\"1.5\" vector-effect=\"non-scaling-stroke\" points=\"");
+    let points: Vec<String> = polygon
+        .vertices()
+        .iter()
+        .map(|v| format!("{},{}", v[0] as f64 + 0.5, v[1] as f64 + 0.5))
+        .collect();
+    svg.push_str(&points.join(" "));
+    svg.push_str("\"/>\n");
+    let mut summary = String::new();
+    for (label, rect, color, dash) in highlights {
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"{}\" \
+             stroke-width=\"2\" {}vector-effect=\"non-scaling-stroke\"><title>{}: {}</title></rect>\n",
+            rect.min[0],
+            rect.min[1],
+            rect.max[0] as i64 - rect.min[0] as i64 + 1,
+            rect.max[1] as i64 - rect.min[1] as i64 + 1,
+            color,
+            dash,
+            label,
+            rect.area()
+        ));
+        summary.push_str(&format!(
+            "{}: {} tiles, {},{} to {},{}\n",
+            label,
+            rect.area(),
+            rect.min[0],
+            rect.min[1],
+            rect.max[0],
+            rect.max[1]
+        ));
+    }
+    svg.push_str("</svg>\n");
+
+    let Some(path) = out else {
+        return Ok(svg);
+    };
+    std::fs::write(path, svg)
+        .map_err(|err| format!("failed to write {}: {}", path.display(), err))?;
+    eprintln!("Wrote {}", path.display());
+    Ok(summary)
+}
+
+/// Day 8 answers for a custom pair count, with the circuit sizes after that
+/// many connections and the connection that completes the network.
+fn report_day8(
+    input: &str,
+    pairs: usize,
+    metric: advent_25::day08::Metric,
+) -> Result<String, String> {
+    let report = advent_25::day08::circuits(input, pairs, metric)?;
+    let (p1, p2) = report.answers;
+    // Run-length encode the sizes: "5, 4, 2x2, 1x7".
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for size in report.sizes() {
+        match runs.last_mut() {
+            Some((last, count)) if *last == size => *count += 1,
+            _ => runs.push((size, 1)),
+        }
+    }
+    let sizes: Vec<String> = runs
+        .iter()
+        .map(|&(size, count)| match count {
+            1 => size.to_string(),
+            _ => format!("{}x{}", size, count),
+        })
+        .collect();
+    let (a, b) = report.final_pair;
+    Ok(format!(
+        "After {} connections: {} circuits (sizes {})\nPart 1: {}\n\
+         Single circuit after connection {} (boxes {} and {})\nPart 2: {}",
+        report.pairs,
+        report.circuits.len(),
+        sizes.join(", "),
+        p1,
+        report.connected_at,
+        a + 1,
+        b + 1,
+        p2
+    ))
+}
+
+/// One line per day 10 machine with the buttons pressed for the lights and
+/// for the joltages, e.g. `3x(1,3)` for three presses of the button wired to
+/// lights 1 and 3, then the totals of the reachable targets.
+fn presses_day10(input: &str) -> Result<String, String> {
+    let machines = advent_25::day10::presses(input)?;
+    let mut out = String::new();
+    let (mut p1, mut p2) = (0u64, 0u64);
+    for (idx, machine) in machines.iter().enumerate() {
+        let describe = |presses: &Result<Vec<u32>, advent_25::day10::Unreachable>,
+                        total: &mut u64| {
+            let presses = match presses {
+                Ok(presses) => presses,
+                Err(reason) => return format!("unreachable ({})", reason),
+            };
+            let mut text = presses.iter().sum::<u32>().to_string();
+            *total += presses.iter().map(|&c| c as u64).sum::<u64>();
+            for (lights, &count) in machine.buttons.iter().zip(presses) {
+                let wiring: Vec<String> = lights.iter().map(|l| l.to_string()).collect();
+                match count {
+                    0 => continue,
+                    1 => text.push_str(&format!(" ({})", wiring.join(","))),
+                    _ => text.push_str(&format!(" {}x({})", count, wiring.join(","))),
+                }
+            }
+            text
+        };
+        out.push_str(&format!(
+            "Machine {} (line {}): lights {}; joltage {}\n",
+            idx + 1,
+            machine.line,
+            describe(&machine.lights, &mut p1),
+            describe(&machine.joltages, &mut p2)
+        ));
+    }
+    out.push_str(&format_parts(Part::Both, p1, p2));
+    Ok(out)
+}
+
+/// Day 10 totals without the machines whose target cannot be reached, each
+/// listed after the answer with the reason.
+fn skip_day10(input: &str, part: Part) -> Result<String, String> {
+    let totals = advent_25::day10::solve_with(input, advent_25::day10::Policy::Skip)?;
+    let mut out = format_parts(part, totals.part1, totals.part2);
+    for skipped in &totals.skipped {
+        let shown = match part {
+            Part::One => !skipped.joltage,
+            Part::Two => skipped.joltage,
+            Part::Both => true,
+        };
+        if shown {
+            out.push_str(&format!("\nSkipped {}", skipped));
+        }
+    }
+    Ok(out)
+}
+
+/// Day 7 manifold with every cell's background colored by the number of beams
+/// entering it on a log scale, optionally also written as a PNG or SVG heatmap.
+fn render_day7_density(input: &str, out: Option<&Path>) -> Result<String, String> {
+    use advent_25::render::{encode_png, encode_svg, heat_ramp, IndexedImage};
+
+    const LEVELS: usize = 32;
+    // Palette: background, unlit non-empty cell, then the heat ramp.
+    const RAMP_START: usize = 2;
+    let density = advent_25::day07::beam_density(input)?;
+    let max = density.max_count();
+    let log_max = (max as f64).ln();
+    let level = |count: u128| -> Option<usize> {
+        if count == 0 {
+            return None;
+        }
+        let t = if max > 1 {
+            (count as f64).ln() / log_max
+        } else {
+            1.0
+        };
+        Some((t * (LEVELS - 1) as f64).round() as usize)
+    };
+
+    let mut palette = vec![[15, 15, 22], [95, 95, 110]];
+    palette.extend(heat_ramp(LEVELS));
+    let mut text = String::new();
+    let mut image = IndexedImage::new(density.width, density.height, palette.clone());
+    for row in 0..density.height {
+        // Only emit an escape when the color changes.
+        let mut shade = None;
+        for col in 0..density.width {
+            let ch = density.cell(row, col);
+            let lvl = level(density.count(row, col));
+            if lvl != shade {
+                match lvl {
+                    Some(lvl) => {
+                        let [r, g, b] = palette[RAMP_START + lvl];
+                        // Light text on the dark end of the ramp, dark text on the bright end.
+                        let fg = if lvl < LEVELS / 2 { 97 } else { 30 };
+                        text.push_str(&format!("\x1b[{};48;2;{};{};{}m", fg, r, g, b));
+                    }
+                    None => text.push_str("\x1b[0m"),
+                }
+                shade = lvl;
+            }
+            match lvl {
+                Some(lvl) => {
+                    text.push(if ch == b'.' { '|' } else { ch as char });
+                    image.set(col, row, (RAMP_START + lvl) as u8);
+                }
+                None => {
+                    text.push(ch as char);
+                    if ch != b'.' {
+                        image.set(col, row, 1);
+                    }
+                }
+            }
+        }
+        text.push_str("\x1b[0m\n");
+    }
+    text.push_str(&format!(
+        "Max beams entering one cell: {} (log color scale)",
+        max
+    ));
+
+    if let Some(path) = out {
+        let is_svg = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+        let bytes = if is_svg {
+            encode_svg(&image, 8).into_bytes()
+        } else {
+            let scale = if density.width.max(density.height) <= 256 {
+                4
+            } else {
+                1
+            };
+            let mut scaled =
+                IndexedImage::new(density.width * scale, density.height * scale, palette);
+            for row in 0..density.height {
+                for col in 0..density.width {
+                    let color = image.pixels[row * density.width + col];
+                    scaled.fill_cell(col, row, scale, color);
+                }
+            }
+            encode_png(&[scaled], 0)
+        };
+        std::fs::write(path, bytes)
+            .map_err(|err| format!("failed to write {}: {}", path.display(), err))?;
+        eprintln!("Wrote {}", path.display());
+    }
+    Ok(text)
+}
+
 fn emit_profile_outputs(report: &pprof::Report) {
     if let Ok(mut file) = std::fs::File::create("day2_flame.svg") {
         let _ = report.flamegraph(&mut file);
@@ -221,11 +758,88 @@ fn emit_profile_outputs(report: &pprof::Report) {
     }
 }
 
+/// Split `--name value` options from positional arguments.
+fn parse_args(args: impl Iterator<Item = String>) -> (Vec<String>, RunOptions) {
+    let mut positional = Vec::new();
+    let mut options = RunOptions::default();
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        let Some(name) = arg.strip_prefix("--") else {
+            positional.push(arg);
+            continue;
+        };
+        let mut value = |what: &str| {
+            args.next().unwrap_or_else(|| {
+                eprintln!("Option --{} expects {}.\n{}", name, what, USAGE);
+                std::process::exit(1);
+            })
+        };
+        match name {
+            "pick" => options.pick = Some(parse_count(name, &value("a digit count"))),
+            "waves" => options.waves = true,
+            "explain" => options.explain = true,
+            "breakdown" => options.breakdown = true,
+            "density" => options.density = true,
+            "anywhere" => options.anywhere = true,
+            "presses" => options.presses = true,
+            "skip" => options.skip = true,
+            "pairs" => options.pairs = Some(parse_count(name, &value("a pair count"))),
+            "metric" => {
+                use advent_25::day08::Metric;
+                options.metric = Some(match value("a metric name").as_str() {
+                    "euclidean" => Metric::Euclidean,
+                    "manhattan" | "taxicab" => Metric::Manhattan,
+                    "chebyshev" => Metric::Chebyshev,
+                    other => {
+                        eprintln!(
+                            "Unknown day 8 metric '{}', expected 'euclidean', 'manhattan' or 'chebyshev'",
+                            other
+                        );
+                        std::process::exit(1);
+                    }
+                })
+            }
+            "engine" => {
+                options.engine = Some(match value("'queue' or 'bits'").as_str() {
+                    "queue" => advent_25::day04::Engine::Queue,
+                    "bits" | "bit-parallel" => advent_25::day04::Engine::BitParallel,
+                    other => {
+                        eprintln!(
+                            "Unknown day 4 engine '{}', expected 'queue' or 'bits'",
+                            other
+                        );
+                        std::process::exit(1);
+                    }
+                })
+            }
+            "out" => options.out = Some(PathBuf::from(value("a file path"))),
+            _ => {
+                eprintln!("Unknown option '--{}'.\n{}", name, USAGE);
+                std::process::exit(1);
+            }
+        }
+    }
+    (positional, options)
+}
+
+fn parse_count(name: &str, raw: &str) -> usize {
+    match raw.parse() {
+        Ok(n) if n > 0 => n,
+        _ => {
+            eprintln!(
+                "Option --{} expects a positive integer, got '{}'",
+                name, raw
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
 fn parse_day(raw: Option<String>) -> DaySelection {
     let value = match raw {
         Some(v) => v,
         None => {
-            eprintln!("Missing day.\nUsage: advent-25 <day|all> [part|mode] < input.txt");
+            eprintln!("Missing day.\n{}", USAGE);
             std::process::exit(1);
         }
     };
@@ -249,9 +863,23 @@ fn parse_part(raw: Option<String>) -> Part {
     }
 }
 
-fn input_for_day<'a>(day: u8, input_owned: &'a mut String) -> &'a str {
-    if let Some(input) = embedded_input_for_day(day) {
-        return input;
+/// Input named on the command line: a file, `-` for stdin, or with no source
+/// the embedded puzzle input (stdin for days without one).
+fn read_input<'a>(day: u8, source: Option<&str>, input_owned: &'a mut String) -> &'a str {
+    match source {
+        None => {
+            if let Some(input) = embedded_input_for_day(day) {
+                return input;
+            }
+        }
+        Some("-") => {}
+        Some(path) => {
+            *input_owned = std::fs::read_to_string(path).unwrap_or_else(|err| {
+                eprintln!("Failed to read {}: {}", path, err);
+                std::process::exit(1);
+            });
+            return input_owned;
+        }
     }
 
     if let Err(err) = io::stdin().read_to_string(input_owned) {
@@ -333,8 +961,146 @@ fn parse_run_mode(raw: &Option<String>) -> Option<RunMode> {
     }
 }
 
+/// Runner flags that tweak what a single day computes or prints.
+#[derive(Default)]
+struct RunOptions {
+    /// Day 3: number of digits to pick per line.
+    pick: Option<usize>,
+    /// Day 4: show the part 2 removal wave by wave.
+    waves: bool,
+    /// Day 4: which removal engine to run.
+    engine: Option<advent_25::day04::Engine>,
+    /// Day 5: explain each ingredient ID's verdict.
+    explain: bool,
+    /// Day 6: print the per-problem breakdown.
+    breakdown: bool,
+    /// Day 7: render the beam density heatmap.
+    density: bool,
+    /// Day 8: number of closest pairs to connect for part 1.
+    pairs: Option<usize>,
+    /// Day 8: distance metric between junction boxes.
+    metric: Option<advent_25::day08::Metric>,
+    /// Day 9: search rectangles with arbitrary corners.
+    anywhere: bool,
+    /// Day 10: print each machine's press vectors.
+    presses: bool,
+    /// Day 10: skip unreachable machines instead of failing.
+    skip: bool,
+    /// Where to write image output for visualisation options.
+    out: Option<PathBuf>,
+}
+
+impl RunOptions {
+    /// Exit with an error if an option was given for a day it does not affect.
+    fn check_applies_to(&self, selection: DaySelection) {
+        let day = match selection {
+            DaySelection::One(day) => Some(day),
+            DaySelection::All => None,
+        };
+        let reject = |flag: &str, wanted: u8| {
+            if day != Some(wanted) {
+                eprintln!("Option --{} only applies to day {}.", flag, wanted);
+                std::process::exit(1);
+            }
+        };
+        if self.pick.is_some() {
+            reject("pick", 3);
+        }
+        if self.waves {
+            reject("waves", 4);
+        }
+        if self.engine.is_some() {
+            reject("engine", 4);
+            if self.waves {
+                eprintln!(
+                    "Option --engine does not apply to --waves, which replays part 2 in rounds."
+                );
+                std::process::exit(1);
+            }
+        }
+        if self.explain {
+            reject("explain", 5);
+        }
+        if self.breakdown {
+            reject("breakdown", 6);
+        }
+        if self.density {
+            reject("density", 7);
+        }
+        if self.pairs.is_some() {
+            reject("pairs", 8);
+        }
+        if self.metric.is_some() {
+            reject("metric", 8);
+        }
+        if self.anywhere {
+            reject("anywhere", 9);
+        }
+        if self.presses {
+            reject("presses", 10);
+        }
+        if self.skip {
+            reject("skip", 10);
+        }
+        if self.out.is_some() && !self.waves && !self.density {
+            eprintln!("Option --out needs a visualisation option such as --waves or --density.");
+            std::process::exit(1);
+        }
+    }
+}
+
 struct RunOutcome {
     day: u8,
     answer: String,
     elapsed: Option<Duration>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{read_input, render_day9, solve_with_options, RunOptions};
+    use advent_25::Part;
+
+    #[test]
+    fn render_day9_draws_loop_and_rectangles() {
+        // An L-shaped loop left of and above the origin.
+        let svg = render_day9("-3,-3\n0,-3\n0,-1\n-1,-1\n-1,0\n-3,0\n", true, None).unwrap();
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+        assert!(svg.contains("points=\"-2.5,-2.5 0.5,-2.5 0.5,-0.5 -0.5,-0.5 -0.5,0.5 -2.5,0.5\""));
+        assert!(svg.contains("<rect x=\"-3\" y=\"0\" width=\"3\" height=\"1\"/>"));
+        assert!(svg.contains("<title>Part 1: 16</title>"));
+        assert!(svg.contains("<title>Part 2: 12</title>"));
+        assert!(svg.contains("<title>Any corners: 12</title>"));
+    }
+
+    #[test]
+    fn day10_options_read_a_named_input() {
+        let path = std::env::temp_dir().join(format!("advent-25-day10-{}.txt", std::process::id()));
+        std::fs::write(&path, "[#.] (1) {0,1}\n[.#] (1) {0,3}\n").unwrap();
+        let mut owned = String::new();
+        let input = read_input(10, path.to_str(), &mut owned);
+        let skip = RunOptions {
+            skip: true,
+            ..Default::default()
+        };
+        let presses = RunOptions {
+            presses: true,
+            ..Default::default()
+        };
+        let skipped = solve_with_options(10, Part::Both, input, &skip);
+        let listed = solve_with_options(10, Part::Both, input, &presses);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            skipped.unwrap(),
+            "Part 1: 1\nPart 2: 4\n\
+             Skipped machine 1 (line 1): lights unreachable: no button is wired to light 0"
+        );
+        assert_eq!(
+            listed.unwrap(),
+            "Machine 1 (line 1): lights unreachable (no button is wired to light 0); joltage 1 (1)\n\
+             Machine 2 (line 2): lights 1 (1); joltage 3 3x(1)\n\
+             Part 1: 1\n\
+             Part 2: 4"
+        );
+    }
+}
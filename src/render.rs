@@ -0,0 +1,272 @@
+//! Small, dependency-free image encoders used by the runner's visualisation
+//! options. Images are palette-indexed so frames stay compact even though the
+//! deflate stream uses stored (uncompressed) blocks.
+
+/// Palette-indexed raster image with at most 256 colors.
+#[derive(Clone, Debug)]
+pub struct IndexedImage {
+    pub width: usize,
+    pub height: usize,
+    pub palette: Vec<[u8; 3]>,
+    /// Row-major palette indices, `width * height` entries.
+    pub pixels: Vec<u8>,
+}
+
+impl IndexedImage {
+    /// Blank image filled with palette entry 0.
+    pub fn new(width: usize, height: usize, palette: Vec<[u8; 3]>) -> Self {
+        assert!(!palette.is_empty() && palette.len() <= 256);
+        Self {
+            width,
+            height,
+            palette,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    #[inline]
+    pub fn set(&mut self, x: usize, y: usize, color: u8) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    /// Paint a `scale`x`scale` block, handy for drawing one grid cell per block.
+    pub fn fill_cell(&mut self, col: usize, row: usize, scale: usize, color: u8) {
+        for y in row * scale..(row + 1) * scale {
+            let start = y * self.width + col * scale;
+            self.pixels[start..start + scale].fill(color);
+        }
+    }
+}
+
+/// Encode frames as a PNG. A single frame gives a plain PNG; more frames give
+/// an animated PNG (APNG) that loops forever with `delay_ms` per frame. All
+/// frames must share the first frame's size and palette.
+pub fn encode_png(frames: &[IndexedImage], delay_ms: u16) -> Vec<u8> {
+    assert!(!frames.is_empty(), "need at least one frame");
+    let first = &frames[0];
+    let (width, height) = (first.width as u32, first.height as u32);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"\x89PNG\r\n\x1a\n");
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    // Bit depth 8, color type 3 (indexed), default compression/filter, no interlace.
+    ihdr.extend_from_slice(&[8, 3, 0, 0, 0]);
+    write_chunk(&mut out, b"IHDR", &ihdr);
+
+    let plte: Vec<u8> = first.palette.iter().flatten().copied().collect();
+    write_chunk(&mut out, b"PLTE", &plte);
+
+    let animated = frames.len() > 1;
+    if animated {
+        let mut actl = Vec::with_capacity(8);
+        actl.extend_from_slice(&(frames.len() as u32).to_be_bytes());
+        actl.extend_from_slice(&0u32.to_be_bytes());
+        write_chunk(&mut out, b"acTL", &actl);
+    }
+
+    let mut sequence = 0u32;
+    for (idx, frame) in frames.iter().enumerate() {
+        assert!(frame.width == first.width && frame.height == first.height);
+        if animated {
+            let mut fctl = Vec::with_capacity(26);
+            fctl.extend_from_slice(&sequence.to_be_bytes());
+            fctl.extend_from_slice(&width.to_be_bytes());
+            fctl.extend_from_slice(&height.to_be_bytes());
+            fctl.extend_from_slice(&0u32.to_be_bytes());
+            fctl.extend_from_slice(&0u32.to_be_bytes());
+            fctl.extend_from_slice(&delay_ms.to_be_bytes());
+            fctl.extend_from_slice(&1000u16.to_be_bytes());
+            // Dispose: none, blend: source (every frame is a full redraw).
+            fctl.extend_from_slice(&[0, 0]);
+            write_chunk(&mut out, b"fcTL", &fctl);
+            sequence += 1;
+        }
+
+        let data = zlib_stored(&scanlines(frame));
+        if idx == 0 {
+            write_chunk(&mut out, b"IDAT", &data);
+        } else {
+            let mut fdat = Vec::with_capacity(data.len() + 4);
+            fdat.extend_from_slice(&sequence.to_be_bytes());
+            fdat.extend_from_slice(&data);
+            write_chunk(&mut out, b"fdAT", &fdat);
+            sequence += 1;
+        }
+    }
+
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+/// Encode an image as SVG, one `rect` per horizontal run of equal color and
+/// `scale` units per pixel. Runs of palette entry 0 are left to the background
+/// rectangle.
+pub fn encode_svg(image: &IndexedImage, scale: usize) -> String {
+    let hex = |[r, g, b]: [u8; 3]| format!("#{:02x}{:02x}{:02x}", r, g, b);
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\" shape-rendering=\"crispEdges\">\n\
+         <rect width=\"{w}\" height=\"{h}\" fill=\"{bg}\"/>\n",
+        w = image.width * scale,
+        h = image.height * scale,
+        bg = hex(image.palette[0])
+    );
+    for (y, row) in image.pixels.chunks(image.width.max(1)).enumerate() {
+        let mut x = 0;
+        while x < row.len() {
+            let color = row[x];
+            let run = row[x..].iter().take_while(|&&c| c == color).count();
+            if color != 0 {
+                out.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                    x * scale,
+                    y * scale,
+                    run * scale,
+                    scale,
+                    hex(image.palette[color as usize])
+                ));
+            }
+            x += run;
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// `levels` colors ramping from dark blue through teal and green to yellow,
+/// for heatmaps where entry 0 is the coldest.
+pub fn heat_ramp(levels: usize) -> Vec<[u8; 3]> {
+    const STOPS: [[f64; 3]; 4] = [
+        [40.0, 20.0, 90.0],
+        [30.0, 130.0, 140.0],
+        [100.0, 200.0, 90.0],
+        [250.0, 230.0, 40.0],
+    ];
+    (0..levels)
+        .map(|idx| {
+            let t = if levels > 1 {
+                idx as f64 / (levels - 1) as f64
+            } else {
+                1.0
+            };
+            let pos = t * (STOPS.len() - 1) as f64;
+            let lo = (pos.floor() as usize).min(STOPS.len() - 2);
+            let frac = pos - lo as f64;
+            let mut color = [0u8; 3];
+            for (channel, value) in color.iter_mut().enumerate() {
+                let (a, b) = (STOPS[lo][channel], STOPS[lo + 1][channel]);
+                *value = (a + (b - a) * frac).round() as u8;
+            }
+            color
+        })
+        .collect()
+}
+
+/// Prefix every row with filter type 0 (none).
+fn scanlines(image: &IndexedImage) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((image.width + 1) * image.height);
+    for row in image.pixels.chunks(image.width.max(1)) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    raw
+}
+
+/// Wrap `data` in a zlib stream made of stored deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 65_535;
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK * 5 + 11);
+    out.extend_from_slice(&[0x78, 0x01]);
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65_521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the largest run that cannot overflow before reducing.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc32, encode_png, encode_svg, heat_ramp, IndexedImage};
+
+    #[test]
+    fn checksums_match_reference_values() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn animated_png_has_one_frame_control_per_frame() {
+        let mut a = IndexedImage::new(3, 2, vec![[0, 0, 0], [255, 255, 255]]);
+        let b = a.clone();
+        a.set(1, 1, 1);
+        let png = encode_png(&[a, b], 100);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        let count = |tag: &[u8]| png.windows(4).filter(|w| *w == tag).count();
+        assert_eq!(count(b"acTL"), 1);
+        assert_eq!(count(b"fcTL"), 2);
+        assert_eq!(count(b"fdAT"), 1);
+        assert!(png.ends_with(&[0xAE, 0x42, 0x60, 0x82]));
+    }
+
+    #[test]
+    fn svg_merges_runs_and_ramp_spans_endpoints() {
+        let mut image = IndexedImage::new(4, 1, vec![[0, 0, 0], [255, 0, 16]]);
+        image.set(1, 0, 1);
+        image.set(2, 0, 1);
+        let svg = encode_svg(&image, 3);
+        assert!(svg.contains("<rect x=\"3\" y=\"0\" width=\"6\" height=\"3\" fill=\"#ff0010\"/>"));
+        assert_eq!(svg.matches("<rect").count(), 2);
+
+        let ramp = heat_ramp(5);
+        assert_eq!(ramp.len(), 5);
+        assert_eq!((ramp[0], ramp[4]), ([40, 20, 90], [250, 230, 40]));
+    }
+}
@@ -0,0 +1,25 @@
+//! Small deterministic generator for the randomised tests.
+
+/// Marsaglia's xorshift64. Good enough to build test inputs; not for anything
+/// that needs statistical quality.
+pub(crate) struct XorShift(u64);
+
+impl XorShift {
+    /// `seed` must be non-zero, or every draw is zero.
+    pub(crate) fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "xorshift needs a non-zero seed");
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Next draw reduced below `bound`.
+    pub(crate) fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}